@@ -1,23 +1,37 @@
 use std::f32::consts::PI;
+use crate::types::Crosshair;
 use crate::types::Item;
+use crate::types::ItemContext;
 use crate::utility::get_root_node;
 use crate::utility::load_model;
+use crate::utility::unload_model;
 
 use pge::*;
+use rand::Rng;
+
+const MAGAZINE_SIZE: u32 = 30;
+const RELOAD_TIME: f32 = 2.0;
+const MIN_SPREAD: f32 = 0.005;
+const MAX_SPREAD: f32 = 0.08;
+const SPREAD_PER_SHOT: f32 = 0.01;
+/// Radians of spread recovered per second.
+const SPREAD_RECOVERY: f32 = 0.1;
 
 pub struct AK47 {
 	node_id: ArenaId<Node>,
+	model_id: ArenaId<Model>,
 	shooting: bool,
 	since_last_shot: f32,
-	bullet_mesh_id: ArenaId<Mesh>,
-	scene_id: ArenaId<Scene>,
+	magazine: u32,
+	reserve: u32,
+	/// Seconds left until the reload finishes.
+	reloading: Option<f32>,
+	spread: f32,
 }
 
 impl AK47 {
 	pub fn new(state: &mut State, scene_id: ArenaId<Scene>) -> Self {
-		let cube = cube(0.3);
-		let mesh_id = state.meshes.insert(cube);
-		let node_id = load_model("assets/ak47.glb", state);
+		let (node_id, model_id) = load_model("assets/ak47.glb", state);
 		let node = state.nodes.get_mut(&node_id).unwrap();
 		node.translation = Vec3::new(0.3, -1.0, 1.0);
 		// rotate 180 degrees
@@ -26,15 +40,22 @@ impl AK47 {
 
 		Self {
 			node_id,
-			scene_id,
+			model_id,
 			shooting: false,
 			since_last_shot: 0.0,
-			bullet_mesh_id: mesh_id,
+			magazine: MAGAZINE_SIZE,
+			reserve: MAGAZINE_SIZE * 4,
+			reloading: None,
+			spread: MIN_SPREAD,
 		}
 	}
 }
 
 impl Item for AK47 {
+	fn name(&self) -> &str {
+		"AK-47"
+	}
+
 	fn prepare(&mut self, state: &mut pge::State) {
 	}
 
@@ -48,6 +69,10 @@ impl Item for AK47 {
 		node.parent = NodeParent::Orphan;
 	}
 
+	fn destroy(&mut self, state: &mut pge::State) {
+		unload_model(state, self.node_id, self.model_id);
+	}
+
 	fn start_primary_action(&mut self, state: &mut State) {
 		log::info!("start shooting");
 		self.shooting = true;
@@ -58,7 +83,36 @@ impl Item for AK47 {
 		self.shooting = false;
 	}
 
-	fn process(&mut self, state: &mut State, dt: f32) {
+	fn reload(&mut self) {
+		if self.reloading.is_none() && self.magazine < MAGAZINE_SIZE && self.reserve > 0 {
+			log::info!("reload");
+			self.reloading = Some(RELOAD_TIME);
+		}
+	}
+
+	fn ammo(&self) -> Option<(u32, u32)> {
+		Some((self.magazine, self.reserve))
+	}
+
+	fn crosshair(&self) -> Crosshair {
+		Crosshair::Spread(self.spread)
+	}
+
+	fn process(&mut self, ctx: &mut ItemContext, dt: f32) {
+		self.spread = (self.spread - SPREAD_RECOVERY * dt).max(MIN_SPREAD);
+
+		if let Some(remaining) = self.reloading {
+			let remaining = remaining - dt;
+			if remaining > 0.0 {
+				self.reloading = Some(remaining);
+				return;
+			}
+			let rounds = (MAGAZINE_SIZE - self.magazine).min(self.reserve);
+			self.magazine += rounds;
+			self.reserve -= rounds;
+			self.reloading = None;
+		}
+
 		if !self.shooting {
 			return;
 		}
@@ -66,31 +120,26 @@ impl Item for AK47 {
 		self.since_last_shot += dt;
 
 		if self.since_last_shot > 0.1 {
+			if self.magazine == 0 {
+				self.reload();
+				return;
+			}
+			self.magazine -= 1;
 			self.since_last_shot = 0.0;
 			log::info!("shoot");
-			let root_node_id = get_root_node(&state, self.node_id);
-			let root_node = state.nodes.get_mut(&root_node_id).unwrap();
+			let root_node_id = get_root_node(ctx.state, self.node_id);
+			let root_node = ctx.state.nodes.get_mut(&root_node_id).unwrap();
 			let (a, mut b, c) = root_node.rotation.to_euler(EulerRot::YXZ);
 			b += -2.0_f32.to_radians();
 			let new_rotation = Quat::from_euler(EulerRot::YXZ, a, b, c);
 			root_node.rotation = new_rotation;
-			let mut bullet_node = Node::new();
-			bullet_node.mesh = Some(self.bullet_mesh_id);
-			bullet_node.parent = NodeParent::Scene(self.scene_id);
-			let mut translation = root_node.translation;
-			translation += root_node.rotation * Vec3::new(0.0, 0.0, 3.0);
-			bullet_node.translation = translation;
-			//bullet_node.translation = ak_node.translation;
-			// let mut translation = ak_node.translation;
-			// translation += ak_node.rotation * Vec3::new(0.0, 0.0, 3.0);
-			let dir = root_node.rotation * Vec3::new(0.0, 0.0, 1.0);
-			bullet_node.physics.velocity = dir * 100.0;
-			bullet_node.physics.mass = 1.0;
-			bullet_node.physics.typ = PhycisObjectType::Dynamic;
-			bullet_node.collision_shape = Some(CollisionShape::Box { size: Vec3::new(0.1, 0.1, 0.1) });
-			state.nodes.insert(bullet_node);
-
-			// bullet_node.translation = node.translation * 
+			let translation = root_node.translation + root_node.rotation * Vec3::new(0.0, 0.0, 3.0);
+			let mut rng = rand::thread_rng();
+			let spread = self.spread * ctx.spread_multiplier;
+			let deviation = Quat::from_euler(EulerRot::YXZ, rng.gen_range(-spread..=spread), rng.gen_range(-spread..=spread), 0.0);
+			let dir = root_node.rotation * deviation * Vec3::new(0.0, 0.0, 1.0);
+			self.spread = (self.spread + SPREAD_PER_SHOT).min(MAX_SPREAD);
+			ctx.combat.fire(ctx.state, ctx.owner, ctx.team, translation, dir, 100.0, 20);
 		}
 	}
 }
\ No newline at end of file
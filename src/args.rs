@@ -1,17 +1,52 @@
 use clap::Parser;
 use clap::Subcommand;
 
+use crate::classes::WizardClass;
+
 
 #[derive(Debug, Parser)]
 #[clap(name = "w")]
 pub struct Args {
     #[clap(subcommand)]
     pub command: Option<Command>,
+    /// Seed for the generated map. A random one is used if omitted.
+    #[clap(long)]
+    pub seed: Option<u64>,
+    /// Overrides the log level from the settings file
+    #[clap(long)]
+    pub log_level: Option<String>,
+    #[clap(long)]
+    pub width: Option<u32>,
+    #[clap(long)]
+    pub height: Option<u32>,
+    #[clap(long, conflicts_with = "windowed")]
+    pub fullscreen: bool,
+    /// Turns fullscreen off even if the settings file has it on
+    #[clap(long)]
+    pub windowed: bool,
+    /// Vertical field of view in degrees
+    #[clap(long)]
+    pub fov: Option<f32>,
+    #[clap(long)]
+    pub sensitivity: Option<f32>,
+    /// Overrides the class from the settings file
+    #[clap(long, value_enum)]
+    pub class: Option<WizardClass>,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
+    /// Roam the generated map without mobs
+    Pvp,
+    /// Show a glTF model on its own
     Inspect {
         path: String,
+    },
+    /// List the local high score table
+    Scores {
+        #[clap(long)]
+        map: Option<String>,
+        #[clap(long)]
+        seed: Option<u64>,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,113 @@
+use pge::*;
+
+use crate::combat::AreaEffect;
+use crate::combat::Combat;
+use crate::combat::Falloff;
+use crate::combat::Target;
+use crate::effects::StatusEffect;
+use crate::npc::Npc;
+use crate::player::Player;
+
+const SLAM_RADIUS: f32 = 8.0;
+/// Seconds the slam stuns whoever it hits.
+const SLAM_STUN: f32 = 1.0;
+/// Hits everyone around the boss, the player and minions alike.
+const SLAM: AreaEffect = AreaEffect {
+	radius: SLAM_RADIUS,
+	damage: 25,
+	falloff: Falloff::None,
+	impulse: 0.0,
+	status: Some(StatusEffect::stunned(SLAM_STUN)),
+};
+const SUMMON_INTERVAL: f32 = 10.0;
+const SUMMON_COUNT: u32 = 2;
+/// Health fractions at which the boss starts summoning and enrages.
+const SUMMON_THRESHOLD: f32 = 0.66;
+const ENRAGE_THRESHOLD: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BossPhase {
+	/// Chases the player and slams when close.
+	Hunting,
+	/// Keeps slamming and calls in adds every now and then.
+	Summoning,
+	/// Below the enrage threshold: faster and slams more often.
+	Enraged,
+}
+
+/// Something the boss wants done that it can't do on its own.
+pub enum BossAction {
+	Summon { count: u32 },
+}
+
+pub struct Boss {
+	pub npc: Npc,
+	pub phase: BossPhase,
+	since_last_slam: f32,
+	since_last_summon: f32,
+}
+
+impl Boss {
+	pub fn new(npc: Npc) -> Self {
+		Self {
+			npc,
+			phase: BossPhase::Hunting,
+			since_last_slam: 0.0,
+			since_last_summon: 0.0,
+		}
+	}
+
+	pub fn health_fraction(&self) -> f32 {
+		self.npc.player.health as f32 / self.npc.player.max_health as f32
+	}
+
+	fn update_phase(&mut self) {
+		let health = self.health_fraction();
+		let phase = if health <= ENRAGE_THRESHOLD {
+			BossPhase::Enraged
+		} else if health <= SUMMON_THRESHOLD {
+			BossPhase::Summoning
+		} else {
+			BossPhase::Hunting
+		};
+
+		if phase != self.phase {
+			log::info!("boss enters phase {:?}", phase);
+			if phase == BossPhase::Enraged {
+				self.npc.player.movement.max_speed *= 2.0;
+			}
+			self.phase = phase;
+		}
+	}
+
+	pub fn process(&mut self, state: &mut State, enemy: &Player, targets: &[Target], combat: &mut Combat, dt: f32) -> Vec<BossAction> {
+		let mut actions = Vec::new();
+		self.update_phase();
+		self.npc.process(state, targets, combat, dt);
+		if !self.npc.player.effects.can_act() {
+			return actions;
+		}
+
+		self.since_last_slam += dt;
+		self.since_last_summon += dt;
+
+		let slam_interval = match self.phase {
+			BossPhase::Enraged => 3.0,
+			_ => 6.0,
+		};
+
+		let center = state.nodes.get(&self.npc.player.node_id).unwrap().translation;
+		let enemy_translation = state.nodes.get(&enemy.node_id).unwrap().translation;
+		if self.since_last_slam >= slam_interval && center.distance(enemy_translation) <= SLAM_RADIUS {
+			self.since_last_slam = 0.0;
+			combat.explode(self.npc.player.node_id, self.npc.player.team, center, SLAM);
+		}
+
+		if self.phase != BossPhase::Hunting && self.since_last_summon >= SUMMON_INTERVAL {
+			self.since_last_summon = 0.0;
+			actions.push(BossAction::Summon { count: SUMMON_COUNT });
+		}
+
+		actions
+	}
+}
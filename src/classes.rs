@@ -0,0 +1,96 @@
+use pge::*;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::ak47::AK47;
+use crate::katana::Katana;
+use crate::player::Player;
+use crate::spells::Spell;
+use crate::spells::SpellDefinition;
+use crate::types::Item;
+
+/// Picked before the game starts, decides what the player spawns with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum WizardClass {
+	/// Sturdy all-rounder who mixes guns and blades with a little magic.
+	#[default]
+	Battlemage,
+	/// Fragile but with a deep mana pool for fire spells.
+	Pyromancer,
+	/// Slows and keeps enemies at range.
+	FrostMage,
+	/// Lets summoned minions do the fighting.
+	Conjurer,
+}
+
+impl WizardClass {
+	pub fn name(&self) -> &'static str {
+		match self {
+			WizardClass::Battlemage => "Battlemage",
+			WizardClass::Pyromancer => "Pyromancer",
+			WizardClass::FrostMage => "Frost Mage",
+			WizardClass::Conjurer => "Conjurer",
+		}
+	}
+
+	/// The spell the class is built around, always in the first slot.
+	pub fn signature(&self) -> SpellDefinition {
+		match self {
+			WizardClass::Battlemage => SpellDefinition::haste(),
+			WizardClass::Pyromancer => SpellDefinition::fireball(),
+			WizardClass::FrostMage => SpellDefinition::frost_bolt(),
+			WizardClass::Conjurer => SpellDefinition::summon_minion(),
+		}
+	}
+
+	/// Sets the class stats and fills the player's empty inventory. Speed is relative to
+	/// the player's movement params.
+	pub fn apply(&self, state: &mut State, scene_id: ArenaId<Scene>, player: &mut Player) {
+		let (health, mana, mana_regen, speed) = match self {
+			WizardClass::Battlemage => (150, 60.0, 4.0, 0.92),
+			WizardClass::Pyromancer => (90, 140.0, 7.0, 1.0),
+			WizardClass::FrostMage => (100, 120.0, 6.0, 1.0),
+			WizardClass::Conjurer => (80, 150.0, 8.0, 0.96),
+		};
+		player.health = health;
+		player.max_health = health;
+		player.mana = mana;
+		player.max_mana = mana;
+		player.mana_regen = mana_regen;
+		player.movement.max_speed *= speed;
+
+		give(player, Spell::new(self.signature()), state);
+		match self {
+			WizardClass::Battlemage => {
+				give(player, AK47::new(state, scene_id), state);
+				give(player, Katana::new(state, scene_id), state);
+				give(player, Spell::new(SpellDefinition::arcane_shield()), state);
+				give(player, Spell::new(SpellDefinition::blink()), state);
+			},
+			WizardClass::Pyromancer => {
+				give(player, Katana::new(state, scene_id), state);
+				give(player, Spell::new(SpellDefinition::arcane_shield()), state);
+				give(player, Spell::new(SpellDefinition::blink()), state);
+			},
+			WizardClass::FrostMage => {
+				give(player, AK47::new(state, scene_id), state);
+				give(player, Spell::new(SpellDefinition::arcane_shield()), state);
+				give(player, Spell::new(SpellDefinition::blink()), state);
+			},
+			WizardClass::Conjurer => {
+				give(player, AK47::new(state, scene_id), state);
+				give(player, Spell::new(SpellDefinition::frost_bolt()), state);
+				give(player, Spell::new(SpellDefinition::arcane_shield()), state);
+			},
+		}
+	}
+}
+
+/// Adds the item to the inventory, or frees it again if there is no room.
+fn give(player: &mut Player, item: impl Item + 'static, state: &mut State) {
+	if let Err(mut item) = player.inventory.add_item(item) {
+		log::warn!("Inventory full, dropping {}", item.name());
+		item.destroy(state);
+	}
+}
@@ -0,0 +1,345 @@
+use pge::*;
+
+use crate::effects::StatusEffect;
+use crate::utility::despawn_node;
+use crate::utility::overlaps_static;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Falloff {
+	/// Full effect everywhere in the radius.
+	None,
+	/// Full effect at the center down to nothing at the edge.
+	Linear,
+}
+
+/// Damage and knockback for everything inside a sphere.
+#[derive(Debug, Clone, Copy)]
+pub struct AreaEffect {
+	pub radius: f32,
+	pub damage: u32,
+	pub falloff: Falloff,
+	/// Velocity change for a node of mass 1 at full effect, heavier nodes move less.
+	pub impulse: f32,
+	/// Applied to every target that takes damage.
+	pub status: Option<StatusEffect>,
+}
+
+impl AreaEffect {
+	/// How much of the effect reaches something `distance` away from the center.
+	pub fn scale(&self, distance: f32) -> f32 {
+		match self.falloff {
+			Falloff::None => 1.0,
+			Falloff::Linear => (1.0 - distance / self.radius).clamp(0.0, 1.0),
+		}
+	}
+}
+
+pub struct Projectile {
+	pub node_id: ArenaId<Node>,
+	pub owner: ArenaId<Node>,
+	pub team: u32,
+	pub damage: u32,
+	pub radius: f32,
+	pub lifetime: f32,
+	/// Explodes on impact or when its lifetime runs out instead of hitting one target.
+	pub explosion: Option<AreaEffect>,
+	pub status: Option<StatusEffect>,
+	pub attack: u32,
+}
+
+/// Instant area hit resolved on the next `Combat::process`, used for melee attacks and
+/// explosions.
+pub struct Strike {
+	pub owner: ArenaId<Node>,
+	pub team: u32,
+	pub translation: Vec3,
+	pub effect: AreaEffect,
+	pub attack: u32,
+}
+
+/// Something projectiles can hit. Projectiles never hit targets on their own team.
+pub struct Target {
+	pub node_id: ArenaId<Node>,
+	pub team: u32,
+	pub radius: f32,
+}
+
+/// Absorbs damage before it reaches health, until it is used up or runs out of time.
+#[derive(Debug, Clone, Copy)]
+pub struct Shield {
+	pub amount: u32,
+	/// Seconds left.
+	pub remaining: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Hit {
+	pub target: ArenaId<Node>,
+	pub owner: ArenaId<Node>,
+	pub damage: u32,
+	pub status: Option<StatusEffect>,
+	/// Shot or strike that caused the hit. One explosion or swing can hit several targets.
+	/// Zero for burns.
+	pub attack: u32,
+	/// Damage from burning rather than from the attack itself.
+	pub burn: bool,
+}
+
+/// Shared damage path for everything that shoots, players and mobs alike.
+pub struct Combat {
+	scene_id: ArenaId<Scene>,
+	bullet_mesh_id: ArenaId<Mesh>,
+	projectiles: Vec<Projectile>,
+	strikes: Vec<Strike>,
+	fired: Vec<ArenaId<Node>>,
+	/// Burn damage dealt since the last `process`.
+	burns: Vec<Hit>,
+	next_attack: u32,
+}
+
+impl Combat {
+	pub fn new(state: &mut State, scene_id: ArenaId<Scene>) -> Self {
+		let bullet_mesh_id = state.meshes.insert(cube(0.3));
+
+		Self {
+			scene_id,
+			bullet_mesh_id,
+			projectiles: Vec::new(),
+			strikes: Vec::new(),
+			fired: Vec::new(),
+			burns: Vec::new(),
+			next_attack: 0,
+		}
+	}
+
+	/// Removes the projectiles in flight and the bullet mesh.
+	pub fn destroy(&mut self, state: &mut State) {
+		for projectile in self.projectiles.drain(..) {
+			despawn_node(state, projectile.node_id);
+		}
+		self.strikes.clear();
+		self.burns.clear();
+		state.meshes.remove(&self.bullet_mesh_id);
+	}
+
+	pub fn scene_id(&self) -> ArenaId<Scene> {
+		self.scene_id
+	}
+
+	pub fn fire(&mut self, state: &mut State, owner: ArenaId<Node>, team: u32, translation: Vec3, dir: Vec3, speed: f32, damage: u32) {
+		self.spawn_projectile(state, owner, team, translation, dir * speed, damage, 3.0, None, None);
+	}
+
+	/// Like `fire`, but the target also gets a status effect.
+	pub fn fire_with_status(&mut self, state: &mut State, owner: ArenaId<Node>, team: u32, translation: Vec3, dir: Vec3, speed: f32, damage: u32, status: StatusEffect) {
+		self.spawn_projectile(state, owner, team, translation, dir * speed, damage, 3.0, None, Some(status));
+	}
+
+	/// Fires a projectile that explodes on impact, or after `fuse` seconds at the latest.
+	pub fn fire_explosive(&mut self, state: &mut State, owner: ArenaId<Node>, team: u32, translation: Vec3, velocity: Vec3, fuse: f32, effect: AreaEffect) {
+		self.spawn_projectile(state, owner, team, translation, velocity, 0, fuse, Some(effect), None);
+	}
+
+	fn spawn_projectile(&mut self, state: &mut State, owner: ArenaId<Node>, team: u32, translation: Vec3, velocity: Vec3, damage: u32, lifetime: f32, explosion: Option<AreaEffect>, status: Option<StatusEffect>) {
+		let mut bullet_node = Node::new();
+		bullet_node.mesh = Some(self.bullet_mesh_id);
+		bullet_node.parent = NodeParent::Scene(self.scene_id);
+		bullet_node.translation = translation;
+		bullet_node.physics.velocity = velocity;
+		bullet_node.physics.mass = 1.0;
+		bullet_node.physics.typ = PhycisObjectType::Dynamic;
+		bullet_node.collision_shape = Some(CollisionShape::Box { size: Vec3::new(0.1, 0.1, 0.1) });
+		let node_id = state.nodes.insert(bullet_node);
+
+		let attack = self.next_attack();
+		self.projectiles.push(Projectile {
+			node_id,
+			owner,
+			team,
+			damage,
+			radius: 0.1,
+			lifetime,
+			explosion,
+			status,
+			attack,
+		});
+		self.fired.push(owner);
+	}
+
+	pub fn strike(&mut self, owner: ArenaId<Node>, team: u32, translation: Vec3, radius: f32, damage: u32) {
+		let effect = AreaEffect {
+			radius,
+			damage,
+			falloff: Falloff::None,
+			impulse: 0.0,
+			status: None,
+		};
+		self.explode(owner, team, translation, effect);
+		self.fired.push(owner);
+	}
+
+	/// Damages every enemy of `team` in range and pushes all dynamic nodes away from the center.
+	pub fn explode(&mut self, owner: ArenaId<Node>, team: u32, translation: Vec3, effect: AreaEffect) {
+		let attack = self.next_attack();
+		self.strikes.push(Strike {
+			owner,
+			team,
+			translation,
+			effect,
+			attack,
+		});
+	}
+
+	/// Burn damage on `target`, returned as a hit by the next `process`.
+	pub fn burn(&mut self, target: ArenaId<Node>, owner: ArenaId<Node>, damage: u32) {
+		self.burns.push(Hit {
+			target,
+			owner,
+			damage,
+			status: None,
+			attack: 0,
+			burn: true,
+		});
+	}
+
+	fn next_attack(&mut self) -> u32 {
+		self.next_attack = self.next_attack.wrapping_add(1);
+		self.next_attack
+	}
+
+	/// Owners of every shot and strike since the last call, one entry per attack.
+	pub fn take_fired(&mut self) -> Vec<ArenaId<Node>> {
+		std::mem::take(&mut self.fired)
+	}
+
+	/// Moves projectiles forward in time and returns every hit that landed this frame.
+	/// Applying the damage is up to the caller since only it knows who owns the target.
+	pub fn process(&mut self, state: &mut State, targets: &[Target], dt: f32) -> Vec<Hit> {
+		let mut hits = std::mem::take(&mut self.burns);
+
+		for projectile in &mut self.projectiles {
+			projectile.lifetime -= dt;
+
+			let translation = match state.nodes.get(&projectile.node_id) {
+				Some(node) => node.translation,
+				None => {
+					projectile.lifetime = 0.0;
+					continue;
+				}
+			};
+
+			let mut impact = false;
+			for target in targets {
+				if target.team == projectile.team {
+					continue;
+				}
+
+				let target_translation = match state.nodes.get(&target.node_id) {
+					Some(node) => node.translation,
+					None => continue,
+				};
+
+				if translation.distance(target_translation) < target.radius + projectile.radius {
+					if projectile.explosion.is_none() {
+						hits.push(Hit {
+							target: target.node_id,
+							owner: projectile.owner,
+							damage: projectile.damage,
+							status: projectile.status,
+							attack: projectile.attack,
+							burn: false,
+						});
+					}
+					impact = true;
+					break;
+				}
+			}
+
+			if let Some(effect) = projectile.explosion {
+				if !impact {
+					impact = overlaps_static(state, self.scene_id, translation, Vec3::splat(projectile.radius));
+				}
+				if impact || projectile.lifetime <= 0.0 {
+					self.strikes.push(Strike {
+						owner: projectile.owner,
+						team: projectile.team,
+						translation,
+						effect,
+						attack: projectile.attack,
+					});
+				}
+			}
+
+			if impact {
+				projectile.lifetime = 0.0;
+			}
+		}
+
+		for strike in self.strikes.drain(..) {
+			for target in targets {
+				if target.team == strike.team {
+					continue;
+				}
+
+				let target_translation = match state.nodes.get(&target.node_id) {
+					Some(node) => node.translation,
+					None => continue,
+				};
+
+				let distance = strike.translation.distance(target_translation);
+				if distance >= target.radius + strike.effect.radius {
+					continue;
+				}
+				let damage = strike.effect.damage as f32 * strike.effect.scale((distance - target.radius).max(0.0));
+				let damage = damage.round() as u32;
+				if damage > 0 {
+					hits.push(Hit {
+						target: target.node_id,
+						owner: strike.owner,
+						damage,
+						status: strike.effect.status,
+						attack: strike.attack,
+						burn: false,
+					});
+				}
+			}
+
+			if strike.effect.impulse > 0.0 {
+				push_away(state, self.scene_id, &self.projectiles, strike.translation, &strike.effect);
+			}
+		}
+
+		for projectile in &self.projectiles {
+			if projectile.lifetime <= 0.0 {
+				despawn_node(state, projectile.node_id);
+			}
+		}
+		self.projectiles.retain(|p| p.lifetime > 0.0);
+
+		hits
+	}
+}
+
+/// Knocks the dynamic nodes of the scene inside the effect's radius away from the center.
+/// That is players, mobs and dropped items. Map geometry is static and stays put, and
+/// projectiles keep their course.
+fn push_away(state: &mut State, scene_id: ArenaId<Scene>, projectiles: &[Projectile], center: Vec3, effect: &AreaEffect) {
+	for (node_id, node) in state.nodes.iter_mut() {
+		if node.parent != NodeParent::Scene(scene_id) || node.physics.typ != PhycisObjectType::Dynamic {
+			continue;
+		}
+		if projectiles.iter().any(|p| p.node_id == node_id) {
+			continue;
+		}
+
+		let offset = node.translation - center;
+		let distance = offset.length();
+		if distance >= effect.radius {
+			continue;
+		}
+
+		// Lift things a little so they don't just grind along the floor
+		let dir = (offset.normalize_or_zero() + Vec3::new(0.0, 0.5, 0.0)).normalize();
+		node.physics.velocity += dir * effect.impulse * effect.scale(distance) / node.physics.mass.max(1.0);
+	}
+}
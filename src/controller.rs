@@ -1,19 +1,352 @@
+use pge::*;
 
+use crate::input::Action;
+use crate::input::Bindings;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::utility::cast_ray;
+use crate::utility::MoveDirection;
+
+/// Height of the third person camera pivot above the player's center.
+const HEAD_HEIGHT: f32 = 0.6;
+const SHOULDER_OFFSET: f32 = 0.6;
+/// Space kept between the third person camera and the wall it was pulled in front of.
+const CAMERA_RADIUS: f32 = 0.3;
+const SPECTATOR_SPEED: f32 = 15.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+	FirstPerson,
+	/// Over the right shoulder, pulled in when a wall is in the way.
+	ThirdPerson,
+	/// Free flying camera detached from the player.
+	Spectator,
+}
+
+/// Mouse look for a first person node: yaw and pitch with a clamped pitch so the
+/// camera can't flip over.
+pub struct LookController {
+	pub sensitivity: f32,
+	pub invert_y: bool,
+	/// 0 applies mouse movement right away, values towards 1 spread it over more frames.
+	pub smoothing: f32,
+	pub max_pitch: f32,
+	pending_yaw: f32,
+	pending_pitch: f32,
+}
+
+impl LookController {
+	pub fn new() -> Self {
+		Self {
+			sensitivity: 0.002,
+			invert_y: false,
+			smoothing: 0.0,
+			max_pitch: 1.5,
+			pending_yaw: 0.0,
+			pending_pitch: 0.0,
+		}
+	}
+
+	pub fn on_mouse_moved(&mut self, dx: f32, dy: f32) {
+		let dy = if self.invert_y { -dy } else { dy };
+		self.pending_yaw += dx * self.sensitivity;
+		self.pending_pitch += dy * self.sensitivity;
+	}
+
+	/// Applies the look input gathered since the last frame to the node. The current
+	/// rotation is read back from the node so recoil and other changes made to it are kept.
+	pub fn process(&mut self, state: &mut State, node_id: ArenaId<Node>, dt: f32) {
+		let factor = if self.smoothing <= 0.0 {
+			1.0
+		} else {
+			1.0 - self.smoothing.clamp(0.0, 0.99).powf(dt * 60.0)
+		};
+
+		let yaw_delta = self.pending_yaw * factor;
+		let pitch_delta = self.pending_pitch * factor;
+		self.pending_yaw -= yaw_delta;
+		self.pending_pitch -= pitch_delta;
+
+		let node = match state.nodes.get_mut(&node_id) {
+			Some(node) => node,
+			None => return,
+		};
+		let (yaw, pitch, _) = node.rotation.to_euler(EulerRot::YXZ);
+		let yaw = yaw + yaw_delta;
+		let pitch = (pitch + pitch_delta).clamp(-self.max_pitch, self.max_pitch);
+		node.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+	}
+}
+
+/// Controls for a `Player`: moves the camera the player looks through, turns
+/// keys and mouse buttons into actions and applies the ones that drive the player.
 pub struct PlayerController {
-	node_id: pge::ArenaId<pge::Node>,
-	camera_id: pge::ArenaId<pge::Camera>
+	pub node_id: ArenaId<Node>,
+	pub camera_id: ArenaId<Camera>,
+	/// Node the camera is attached to outside of first person.
+	camera_node_id: ArenaId<Node>,
+	scene_id: ArenaId<Scene>,
+	mode: CameraMode,
+	third_person_distance: f32,
+	fly: MoveDirection,
+	pub look: LookController,
+	bindings: Bindings,
+	settings: Settings,
 }
 
 impl PlayerController {
-	pub fn new(state: &mut pge::State, node_id: pge::ArenaId<pge::Node>) -> Self {
-		let mut camera = pge::Camera::new();
-		camera.zfar = 1000.0;
-		camera.node_id = Some(node_id);
-		let camera_id = state.cameras.insert(camera);
+	/// `camera_id` is the player's own camera from `PlayerBuilder::camera`, it goes away
+	/// with the player.
+	pub fn new(state: &mut State, scene_id: ArenaId<Scene>, node_id: ArenaId<Node>, camera_id: ArenaId<Camera>, settings: &Settings) -> Self {
+		let mut camera_node = Node::new();
+		camera_node.parent = NodeParent::Scene(scene_id);
+		let camera_node_id = state.nodes.insert(camera_node);
+
+		let mut look = LookController::new();
+		look.sensitivity = settings.mouse.sensitivity;
+		look.invert_y = settings.mouse.invert_y;
+		look.smoothing = settings.mouse.smoothing;
 
 		Self {
 			node_id,
-			camera_id
+			camera_id,
+			camera_node_id,
+			scene_id,
+			mode: CameraMode::FirstPerson,
+			third_person_distance: settings.camera.third_person_distance,
+			fly: MoveDirection::new(),
+			look,
+			bindings: settings.bindings.clone(),
+			settings: settings.clone(),
+		}
+	}
+
+	/// Returns the actions the controller doesn't handle itself, with whether they were pressed.
+	pub fn on_keyboard_input(&mut self, player: &mut Player, key: KeyboardKey, action: KeyAction, state: &mut State) -> Vec<(Action, bool)> {
+		let pressed = match action {
+			KeyAction::Pressed => true,
+			KeyAction::Released => false,
+		};
+		let actions = self.bindings.key_actions(key);
+		self.dispatch(player, actions, pressed, state)
+	}
+
+	/// Returns the actions the controller doesn't handle itself, with whether they were pressed.
+	pub fn on_mouse_input(&mut self, player: &mut Player, event: MouseEvent, state: &mut State) -> Vec<(Action, bool)> {
+		match event {
+			MouseEvent::Moved { dx, dy } => {
+				self.look.on_mouse_moved(dx, dy);
+				if self.mode != CameraMode::Spectator {
+					player.on_mouse_moved(dx, dy, state);
+				}
+				Vec::new()
+			},
+			MouseEvent::Pressed { button } => {
+				let actions = self.bindings.mouse_actions(button);
+				self.dispatch(player, actions, true, state)
+			},
+			MouseEvent::Released { button } => {
+				let actions = self.bindings.mouse_actions(button);
+				self.dispatch(player, actions, false, state)
+			},
+			_ => Vec::new()
+		}
+	}
+
+	fn dispatch(&mut self, player: &mut Player, actions: Vec<Action>, pressed: bool, state: &mut State) -> Vec<(Action, bool)> {
+		let mut unhandled = Vec::new();
+		for action in actions {
+			if !self.on_action(player, action, pressed, state) {
+				unhandled.push((action, pressed));
+			}
+		}
+		unhandled
+	}
+
+	/// Returns false if the action isn't one the controller knows about.
+	fn on_action(&mut self, player: &mut Player, action: Action, pressed: bool, state: &mut State) -> bool {
+		match (action, pressed) {
+			(Action::IncreaseSensitivity, true) => self.set_sensitivity(self.look.sensitivity * 1.1),
+			(Action::DecreaseSensitivity, true) => self.set_sensitivity(self.look.sensitivity / 1.1),
+			(Action::ToggleInvertY, true) => self.set_invert_y(!self.look.invert_y),
+			// Dead players stay in the spectator camera
+			(Action::CycleCamera, true) if !player.death => {
+				player.movdir = MoveDirection::new();
+				self.cycle_mode(state);
+			},
+			(Action::IncreaseSensitivity, false) | (Action::DecreaseSensitivity, false) | (Action::ToggleInvertY, false) | (Action::CycleCamera, _) => {},
+			_ if self.mode == CameraMode::Spectator => return self.on_spectator_action(action, pressed),
+			_ => return self.on_player_action(player, action, pressed, state),
+		}
+		true
+	}
+
+	fn on_spectator_action(&mut self, action: Action, pressed: bool) -> bool {
+		match action {
+			Action::MoveForward => self.fly.forward = pressed,
+			Action::MoveBackward => self.fly.backward = pressed,
+			Action::MoveLeft => self.fly.left = pressed,
+			Action::MoveRight => self.fly.right = pressed,
+			_ => return false,
+		}
+		true
+	}
+
+	fn on_player_action(&mut self, player: &mut Player, action: Action, pressed: bool, state: &mut State) -> bool {
+		if let Some(slot) = action.slot() {
+			if pressed {
+				player.equip(slot, state);
+			}
+			return true;
+		}
+
+		match (action, pressed) {
+			(Action::MoveForward, _) => player.movdir.forward = pressed,
+			(Action::MoveBackward, _) => player.movdir.backward = pressed,
+			(Action::MoveLeft, _) => player.movdir.left = pressed,
+			(Action::MoveRight, _) => player.movdir.right = pressed,
+			(Action::Sprint, true) => player.start_sprinting(state),
+			(Action::Sprint, false) => player.stop_sprinting(state),
+			(Action::Dash, true) => player.dash(),
+			(Action::Dash, false) => {},
+			(Action::Jump, true) => player.jump(state),
+			(Action::Jump, false) => {},
+			(Action::Crouch, true) => player.start_crouching(state),
+			(Action::Crouch, false) => player.stop_crouching(state),
+			(Action::Prone, true) => player.toggle_prone(state),
+			(Action::Prone, false) => {},
+			(Action::PrimaryFire, true) => player.start_primary_action(state),
+			(Action::PrimaryFire, false) => player.stop_primary_action(state),
+			(Action::SecondaryFire, true) => player.start_secondary_action(state),
+			(Action::SecondaryFire, false) => player.stop_secondary_action(state),
+			(Action::ThirdAction, true) => player.start_third_action(state),
+			(Action::ThirdAction, false) => player.stop_third_action(state),
+			(Action::Reload, true) => player.reload(),
+			(Action::Reload, false) => {},
+			(Action::ThrowGrenade, true) => player.throw_grenade(),
+			(Action::ThrowGrenade, false) => {},
+			(Action::Drop, true) => player.drop(state),
+			(Action::Drop, false) => {},
+			(Action::Grab, true) => player.start_grap(state),
+			(Action::Grab, false) => player.stop_grap(state),
+			_ => return false,
 		}
+		true
 	}
-}
\ No newline at end of file
+
+	fn set_sensitivity(&mut self, sensitivity: f32) {
+		log::info!("mouse sensitivity {:.4}", sensitivity);
+		self.look.sensitivity = sensitivity;
+		self.settings.mouse.sensitivity = sensitivity;
+		if let Err(err) = Settings::update(|s| s.mouse.sensitivity = sensitivity) {
+			log::error!("failed to save settings: {}", err);
+		}
+	}
+
+	fn set_invert_y(&mut self, invert_y: bool) {
+		log::info!("invert mouse y {}", invert_y);
+		self.look.invert_y = invert_y;
+		self.settings.mouse.invert_y = invert_y;
+		if let Err(err) = Settings::update(|s| s.mouse.invert_y = invert_y) {
+			log::error!("failed to save settings: {}", err);
+		}
+	}
+
+	/// The settings the game is running with, including changes made in game.
+	pub fn settings(&self) -> &Settings {
+		&self.settings
+	}
+
+	pub fn mode(&self) -> CameraMode {
+		self.mode
+	}
+
+	fn cycle_mode(&mut self, state: &mut State) {
+		let mode = match self.mode {
+			CameraMode::FirstPerson => CameraMode::ThirdPerson,
+			CameraMode::ThirdPerson => CameraMode::Spectator,
+			CameraMode::Spectator => CameraMode::FirstPerson,
+		};
+		self.set_mode(state, mode);
+	}
+
+	pub fn set_mode(&mut self, state: &mut State, mode: CameraMode) {
+		if mode == self.mode {
+			return;
+		}
+		log::info!("camera mode {:?}", mode);
+
+		// The spectator starts from wherever the camera currently is
+		if mode == CameraMode::Spectator {
+			let view = self.view_node();
+			let transform = state.nodes.get(&view).map(|n| (n.translation, n.rotation));
+			if let (Some((translation, rotation)), Some(camera_node)) = (transform, state.nodes.get_mut(&self.camera_node_id)) {
+				camera_node.translation = translation;
+				camera_node.rotation = rotation;
+			}
+		}
+
+		self.mode = mode;
+		self.fly = MoveDirection::new();
+		let view = self.view_node();
+		if let Some(camera) = state.cameras.get_mut(&self.camera_id) {
+			camera.node_id = Some(view);
+		}
+	}
+
+	fn view_node(&self) -> ArenaId<Node> {
+		match self.mode {
+			CameraMode::FirstPerson => self.node_id,
+			_ => self.camera_node_id,
+		}
+	}
+
+	fn follow_player(&mut self, state: &mut State) {
+		let (translation, rotation) = match state.nodes.get(&self.node_id) {
+			Some(node) => (node.translation, node.rotation),
+			None => return,
+		};
+
+		let pivot = translation + Vec3::new(0.0, HEAD_HEIGHT, 0.0);
+		let offset = rotation * Vec3::new(SHOULDER_OFFSET, 0.0, -self.third_person_distance);
+		let max_distance = offset.length();
+		let dir = offset / max_distance;
+		// Pull the camera in front of any wall between it and the player
+		let distance = match cast_ray(state, self.scene_id, pivot, dir, max_distance) {
+			Some(hit) => (hit - CAMERA_RADIUS).max(0.0),
+			None => max_distance,
+		};
+
+		if let Some(camera_node) = state.nodes.get_mut(&self.camera_node_id) {
+			camera_node.translation = pivot + dir * distance;
+			camera_node.rotation = rotation;
+		}
+	}
+
+	fn fly(&mut self, state: &mut State, dt: f32) {
+		if !self.fly.is_moving() {
+			return;
+		}
+		if let Some(camera_node) = state.nodes.get_mut(&self.camera_node_id) {
+			camera_node.translation += camera_node.rotation * self.fly.to_vec3() * SPECTATOR_SPEED * dt;
+		}
+	}
+
+	pub fn process(&mut self, state: &mut State, dt: f32) {
+		match self.mode {
+			CameraMode::FirstPerson => self.look.process(state, self.node_id, dt),
+			CameraMode::ThirdPerson => {
+				self.look.process(state, self.node_id, dt);
+				self.follow_player(state);
+			},
+			CameraMode::Spectator => {
+				self.look.process(state, self.camera_node_id, dt);
+				self.fly(state, dt);
+			},
+		}
+	}
+
+	pub fn destroy(&mut self, state: &mut State) {
+		state.nodes.remove(&self.camera_node_id);
+	}
+}
@@ -7,7 +7,10 @@ use rand::Rng;
 use crate::types::SurvivalMap;
 
 pub struct DarkDungeon {
-	rng: ThreadRng
+	rng: ThreadRng,
+	meshes: Vec<ArenaId<Mesh>>,
+	materials: Vec<ArenaId<Material>>,
+	textures: Vec<ArenaId<Texture>>,
 }
 
 impl DarkDungeon {
@@ -33,9 +36,10 @@ impl DarkDungeon {
 		// 	[0.0, 1.0],
 		// ];
 		wall_mesh.primitives[0].material = Some(wall_material_id);
+		let wall_mesh_id = state.meshes.insert(wall_mesh);
 
 		let mut forward_wall = Node::new();
-		forward_wall.mesh = Some(state.meshes.insert(wall_mesh.clone()));
+		forward_wall.mesh = Some(wall_mesh_id);
 		forward_wall.translation = Vec3::new(0.0, 0.0, size);
 		forward_wall.looking_at(0.0, 0.0, 0.0);
 		// rotate 90 degrees
@@ -46,7 +50,7 @@ impl DarkDungeon {
 		state.nodes.insert(forward_wall);
 
 		let mut back_wall = Node::new();
-		back_wall.mesh = Some(state.meshes.insert(wall_mesh.clone()));
+		back_wall.mesh = Some(wall_mesh_id);
 		back_wall.translation = Vec3::new(0.0, 0.0, -size);
 		back_wall.looking_at(0.0, 0.0, 0.0);
 		// rotate 90 degrees
@@ -57,7 +61,7 @@ impl DarkDungeon {
 		state.nodes.insert(back_wall);
 
 		let mut left_wall = Node::new();
-		left_wall.mesh = Some(state.meshes.insert(wall_mesh.clone()));
+		left_wall.mesh = Some(wall_mesh_id);
 		left_wall.translation = Vec3::new(-size, 0.0, 0.0);
 		left_wall.rotation = Quat::from_euler(EulerRot::YXZ, 0.0, 0.0, 1.5708);
 		left_wall.collision_shape = Some(CollisionShape::Box { size: Vec3::new(1.0, size, size) });
@@ -67,7 +71,7 @@ impl DarkDungeon {
 		state.nodes.insert(left_wall);
 
 		let mut top_wall = Node::new();
-		top_wall.mesh = Some(state.meshes.insert(wall_mesh.clone()));
+		top_wall.mesh = Some(wall_mesh_id);
 		top_wall.translation = Vec3::new(0.0, size, 0.0);
 		top_wall.rotation = Quat::from_euler(EulerRot::YXZ, 0.0, 0.0, 0.0);
 		// top_wall.collision_shape = Some(CollisionShape::Box { size: Vec3::new(size, size, 1.0) });
@@ -76,7 +80,7 @@ impl DarkDungeon {
 		state.nodes.insert(top_wall);
 
 		let mut right_wall = Node::new();
-		right_wall.mesh = Some(state.meshes.insert(wall_mesh.clone()));
+		right_wall.mesh = Some(wall_mesh_id);
 		right_wall.translation = Vec3::new(size, 0.0, 0.0);
 		right_wall.rotation = Quat::from_euler(EulerRot::YXZ, 0.0, 0.0, 1.5708);
 		right_wall.collision_shape = Some(CollisionShape::Box { size: Vec3::new(1.0, size, size) });
@@ -136,12 +140,23 @@ impl DarkDungeon {
 		}
 
 		Self {
-			rng: thread_rng()
+			rng: thread_rng(),
+			meshes: vec![wall_mesh_id, floor_mesh_id],
+			materials: vec![wall_material_id, ground_material_id],
+			textures: vec![texture_id, orange_texture_id],
 		}
 	}
 }
 
 impl SurvivalMap for DarkDungeon {
+	fn name(&self) -> &str {
+		"dark_dungeon"
+	}
+
+	fn seed(&self) -> u64 {
+		0
+	}
+
 	fn get_mob_spawn_point(&mut self) -> pge::Vec3 {
 		let x = self.rng.gen_range(-25.0..25.0);
 		let z = self.rng.gen_range(-25.0..25.0);
@@ -155,4 +170,16 @@ impl SurvivalMap for DarkDungeon {
 	fn process(&mut self, state: &mut pge::State) {
 		// Do nothing
 	}
+
+	fn destroy(&mut self, state: &mut pge::State) {
+		for mesh_id in self.meshes.drain(..) {
+			state.meshes.remove(&mesh_id);
+		}
+		for material_id in self.materials.drain(..) {
+			state.materials.remove(&material_id);
+		}
+		for texture_id in self.textures.drain(..) {
+			state.textures.remove(&texture_id);
+		}
+	}
 }
\ No newline at end of file
@@ -0,0 +1,224 @@
+use pge::ArenaId;
+use pge::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+	/// Damage over time, stacks up to `MAX_BURNING_STACKS`.
+	Burning,
+	/// Slower movement, turns into frozen after `FREEZE_STACKS` applications.
+	Slowed,
+	/// No movement and no actions.
+	Frozen,
+	/// No actions and no movement input.
+	Stunned,
+	/// Faster movement.
+	Haste,
+}
+
+impl StatusKind {
+	pub fn name(&self) -> &'static str {
+		match self {
+			StatusKind::Burning => "Burning",
+			StatusKind::Slowed => "Slowed",
+			StatusKind::Frozen => "Frozen",
+			StatusKind::Stunned => "Stunned",
+			StatusKind::Haste => "Haste",
+		}
+	}
+}
+
+const MAX_BURNING_STACKS: u32 = 3;
+const FREEZE_STACKS: u32 = 3;
+/// Seconds a target stays frozen once the slows add up.
+const FREEZE_DURATION: f32 = 1.5;
+
+/// An effect to apply. What `magnitude` means depends on the kind: damage per second for
+/// burning, the fraction of speed lost for slowed and the fraction gained for haste.
+#[derive(Debug, Clone, Copy)]
+pub struct StatusEffect {
+	pub kind: StatusKind,
+	pub duration: f32,
+	pub magnitude: f32,
+}
+
+impl StatusEffect {
+	pub const fn burning(damage_per_second: f32, duration: f32) -> Self {
+		Self { kind: StatusKind::Burning, duration, magnitude: damage_per_second }
+	}
+
+	pub const fn slowed(fraction: f32, duration: f32) -> Self {
+		Self { kind: StatusKind::Slowed, duration, magnitude: fraction }
+	}
+
+	pub const fn frozen(duration: f32) -> Self {
+		Self { kind: StatusKind::Frozen, duration, magnitude: 0.0 }
+	}
+
+	pub const fn stunned(duration: f32) -> Self {
+		Self { kind: StatusKind::Stunned, duration, magnitude: 0.0 }
+	}
+
+	pub const fn haste(fraction: f32, duration: f32) -> Self {
+		Self { kind: StatusKind::Haste, duration, magnitude: fraction }
+	}
+}
+
+#[derive(Debug, Clone)]
+struct ActiveEffect {
+	effect: StatusEffect,
+	remaining: f32,
+	stacks: u32,
+}
+
+/// Effects currently on a player or mob. There is at most one entry per kind. Applying a
+/// kind that is already active refreshes its duration and keeps the stronger magnitude,
+/// burning and slowed also gain a stack.
+#[derive(Debug, Clone, Default)]
+pub struct StatusEffects {
+	active: Vec<ActiveEffect>,
+	/// Burn damage not yet dealt because it didn't add up to a whole point.
+	pending_damage: f32,
+	/// Whoever set the target on fire last, credited with the burn damage.
+	burn_source: Option<ArenaId<Node>>,
+}
+
+impl StatusEffects {
+	/// `source` is who applied the effect, if anyone.
+	pub fn apply(&mut self, effect: StatusEffect, source: Option<ArenaId<Node>>) {
+		if effect.kind == StatusKind::Burning && source.is_some() {
+			self.burn_source = source;
+		}
+		if let Some(active) = self.active.iter_mut().find(|a| a.effect.kind == effect.kind) {
+			active.remaining = active.remaining.max(effect.duration);
+			active.effect.magnitude = active.effect.magnitude.max(effect.magnitude);
+			match effect.kind {
+				StatusKind::Burning => active.stacks = (active.stacks + 1).min(MAX_BURNING_STACKS),
+				StatusKind::Slowed => active.stacks += 1,
+				_ => {}
+			}
+			if effect.kind == StatusKind::Slowed && active.stacks >= FREEZE_STACKS {
+				// The slow starts over once the target thaws
+				self.active.retain(|a| a.effect.kind != StatusKind::Slowed);
+				self.apply(StatusEffect::frozen(FREEZE_DURATION), source);
+			}
+			return;
+		}
+
+		self.active.push(ActiveEffect {
+			effect,
+			remaining: effect.duration,
+			stacks: 1,
+		});
+	}
+
+	pub fn has(&self, kind: StatusKind) -> bool {
+		self.active.iter().any(|a| a.effect.kind == kind)
+	}
+
+	pub fn clear(&mut self) {
+		self.active.clear();
+		self.pending_damage = 0.0;
+		self.burn_source = None;
+	}
+
+	pub fn burn_source(&self) -> Option<ArenaId<Node>> {
+		self.burn_source
+	}
+
+	/// Counts down the effects and returns the damage they dealt this frame.
+	pub fn process(&mut self, dt: f32) -> u32 {
+		for active in &mut self.active {
+			if active.effect.kind == StatusKind::Burning {
+				self.pending_damage += active.effect.magnitude * active.stacks as f32 * dt.min(active.remaining);
+			}
+			active.remaining -= dt;
+		}
+		self.active.retain(|a| a.remaining > 0.0);
+
+		let damage = self.pending_damage.floor();
+		self.pending_damage -= damage;
+		damage as u32
+	}
+
+	/// Scales the top speed.
+	pub fn speed_multiplier(&self) -> f32 {
+		let mut multiplier = 1.0;
+		for active in &self.active {
+			match active.effect.kind {
+				StatusKind::Slowed => multiplier *= (1.0 - active.effect.magnitude).max(0.0),
+				StatusKind::Haste => multiplier *= 1.0 + active.effect.magnitude,
+				StatusKind::Frozen => multiplier = 0.0,
+				_ => {}
+			}
+		}
+		multiplier
+	}
+
+	/// Whether items, jumping and dashing can be used.
+	pub fn can_act(&self) -> bool {
+		!self.has(StatusKind::Stunned) && !self.has(StatusKind::Frozen)
+	}
+
+	/// Active kinds with the seconds they have left, for the HUD.
+	pub fn list(&self) -> Vec<(StatusKind, f32)> {
+		self.active.iter().map(|a| (a.effect.kind, a.remaining)).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reapplying_refreshes_duration_and_keeps_stronger_magnitude() {
+		let mut effects = StatusEffects::default();
+		effects.apply(StatusEffect::haste(0.5, 1.0), None);
+		effects.process(0.8);
+		effects.apply(StatusEffect::haste(0.2, 2.0), None);
+		assert_eq!(effects.list(), vec![(StatusKind::Haste, 2.0)]);
+		assert_eq!(effects.speed_multiplier(), 1.5);
+
+		// A shorter application doesn't cut the remaining time
+		effects.apply(StatusEffect::haste(0.2, 0.5), None);
+		assert_eq!(effects.list(), vec![(StatusKind::Haste, 2.0)]);
+	}
+
+	#[test]
+	fn repeated_slows_freeze() {
+		let mut effects = StatusEffects::default();
+		for _ in 0..FREEZE_STACKS - 1 {
+			effects.apply(StatusEffect::slowed(0.4, 3.0), None);
+		}
+		assert!(effects.can_act());
+		effects.apply(StatusEffect::slowed(0.4, 3.0), None);
+		assert_eq!(effects.list(), vec![(StatusKind::Frozen, FREEZE_DURATION)]);
+		assert!(!effects.can_act());
+		assert_eq!(effects.speed_multiplier(), 0.0);
+	}
+
+	#[test]
+	fn burning_stacks_up_to_the_cap() {
+		let mut effects = StatusEffects::default();
+		for _ in 0..5 {
+			effects.apply(StatusEffect::burning(10.0, 5.0), None);
+		}
+		assert_eq!(effects.process(1.0), 10 * MAX_BURNING_STACKS);
+	}
+
+	#[test]
+	fn fractional_burn_damage_adds_up() {
+		let mut effects = StatusEffects::default();
+		effects.apply(StatusEffect::burning(1.0, 10.0), None);
+		let damage: u32 = (0..60).map(|_| effects.process(0.05)).sum();
+		assert_eq!(damage, 3);
+	}
+
+	#[test]
+	fn burning_stops_when_it_runs_out() {
+		let mut effects = StatusEffects::default();
+		effects.apply(StatusEffect::burning(10.0, 0.5), None);
+		assert_eq!(effects.process(1.0), 5);
+		assert!(!effects.has(StatusKind::Burning));
+		assert_eq!(effects.process(1.0), 0);
+	}
+}
@@ -1,15 +1,25 @@
 use pge::*;
+use rand::rngs::StdRng;
 use rand::Rng;
+use rand::SeedableRng;
 
+use crate::types::PVPMap;
 use crate::types::SurvivalMap;
 
 
 pub struct GeneratedPVPMap {
-    rng: rand::rngs::ThreadRng,
+    rng: StdRng,
+    seed: u64,
+    meshes: Vec<ArenaId<Mesh>>,
 }
 
 impl GeneratedPVPMap {
 	pub fn new(state: &mut pge::State, scene_id: ArenaId<Scene>, width: f32, height: f32) -> Self {
+		Self::with_seed(state, scene_id, width, height, rand::random())
+	}
+
+	/// The same seed always generates the same layout.
+	pub fn with_seed(state: &mut pge::State, scene_id: ArenaId<Scene>, width: f32, height: f32, seed: u64) -> Self {
         let mut grid: Vec<bool> = Vec::with_capacity((width * height) as usize);
 
         let object_count = (width * height * 0.025) as usize;
@@ -17,7 +27,7 @@ impl GeneratedPVPMap {
         let cube_mesh = cube(1.0);
         let cube_mesh = state.meshes.insert(cube_mesh);
 
-        let mut rng = rand::thread_rng();
+        let mut rng = StdRng::seed_from_u64(seed);
         let half_width = width / 2.0;
         let half_height = height / 2.0;
         // Initialize grid with false values
@@ -109,6 +119,8 @@ impl GeneratedPVPMap {
 
 		Self {
             rng,
+            seed,
+            meshes: vec![cube_mesh, floor_mesh],
 		}
 	}
 
@@ -118,6 +130,14 @@ impl GeneratedPVPMap {
 }
 
 impl SurvivalMap for GeneratedPVPMap {
+	fn name(&self) -> &str {
+		"generated_pvp"
+	}
+
+	fn seed(&self) -> u64 {
+		self.seed
+	}
+
 	fn get_mob_spawn_point(&mut self) -> pge::Vec3 {
 		let x = self.rng.gen_range(-25.0..25.0);
 		let z = self.rng.gen_range(-25.0..25.0);
@@ -131,4 +151,19 @@ impl SurvivalMap for GeneratedPVPMap {
 	fn process(&mut self, state: &mut pge::State) {
 		// Do nothing
 	}
-}
\ No newline at end of file
+
+	fn destroy(&mut self, state: &mut pge::State) {
+		for mesh_id in self.meshes.drain(..) {
+			state.meshes.remove(&mesh_id);
+		}
+	}
+}
+impl PVPMap for GeneratedPVPMap {
+	fn get_player_spawn_point(&mut self) -> pge::Vec3 {
+		pge::Vec3::new(0.0, 10.0, 0.0)
+	}
+
+	fn process(&mut self, state: &mut pge::State) {
+		// Do nothing
+	}
+}
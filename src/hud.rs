@@ -0,0 +1,247 @@
+use pge::*;
+
+use crate::effects::StatusKind;
+use crate::types::Crosshair;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitMarker {
+	Hit,
+	Kill,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotbarSlot {
+	/// None for an empty slot.
+	pub name: Option<String>,
+	pub active: bool,
+}
+
+/// Everything the HUD shows. Each frame only the elements whose part changed are replaced.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HudState {
+	pub health: f32,
+	pub stamina: f32,
+	pub mana: f32,
+	/// Rounds in the magazine and in reserve for the active weapon.
+	pub ammo: Option<(u32, u32)>,
+	pub wave: u32,
+	pub enemies_remaining: u32,
+	pub boss_health: Option<f32>,
+	pub banner: Option<String>,
+	pub hotbar: Vec<HotbarSlot>,
+	pub crosshair: Crosshair,
+	pub hit_marker: Option<HitMarker>,
+	/// Status effects on the player with whole seconds left.
+	pub effects: Vec<(StatusKind, u32)>,
+}
+
+pub struct Hud {
+	window_id: ArenaId<Window>,
+	camera_id: ArenaId<Camera>,
+	ui_id: ArenaId<GUIElement>,
+	shown: Option<HudState>,
+}
+
+fn bar(fraction: f32, color: Color) -> GUIElement {
+	let fraction = fraction.clamp(0.0, 1.0);
+	row(&[
+		rect().background_color(color).width(fraction),
+		rect().background_color(Color::BLACK).width(1.0 - fraction),
+	])
+}
+
+fn hotbar_cell(slot: &HotbarSlot) -> GUIElement {
+	match &slot.name {
+		Some(name) => {
+			let color = if slot.active { Color::WHITE } else { Color::BLUE };
+			stack(&[
+				rect().background_color(color),
+				text(name),
+			])
+		},
+		None => rect().background_color(Color::BLACK),
+	}
+}
+
+fn crosshair(crosshair: Crosshair, hit_marker: Option<HitMarker>) -> GUIElement {
+	let color = match hit_marker {
+		Some(HitMarker::Kill) => Color::RED,
+		Some(HitMarker::Hit) => Color::CYAN,
+		None => Color::WHITE,
+	};
+	let size = match hit_marker {
+		Some(HitMarker::Kill) => 0.012,
+		Some(HitMarker::Hit) => 0.008,
+		None => 0.004,
+	};
+
+	match crosshair {
+		Crosshair::Dot => rect().background_color(color).width(size).height(size),
+		Crosshair::Spread(spread) => {
+			// Gap between the two halves grows with the spread
+			let gap = spread * 0.25;
+			row(&[
+				rect().background_color(color).width(0.01),
+				rect().width(gap),
+				rect().background_color(color).width(size),
+				rect().width(gap),
+				rect().background_color(color).width(0.01),
+			]).width(0.02 + gap * 2.0 + size).height(0.003)
+		},
+	}
+}
+
+fn effects_text(effects: &[(StatusKind, u32)]) -> GUIElement {
+	let effects: Vec<String> = effects.iter().map(|(kind, seconds)| format!("{} {}s", kind.name(), seconds)).collect();
+	text(&effects.join("  "))
+}
+
+fn ammo_text(ammo: Option<(u32, u32)>) -> GUIElement {
+	match ammo {
+		Some((magazine, reserve)) => text(&format!("{} / {}", magazine, reserve)),
+		None => text(""),
+	}
+}
+
+fn boss_bar(boss_health: Option<f32>) -> GUIElement {
+	match boss_health {
+		Some(health) => bar(health, Color::RED),
+		None => rect(),
+	}
+}
+
+fn center(hud: &HudState) -> GUIElement {
+	match &hud.banner {
+		Some(banner) => row(&[text(banner)]).height(0.1),
+		None => crosshair(hud.crosshair, hud.hit_marker),
+	}
+}
+
+// Where the elements that change sit in the tree, as child indices from the root
+const WAVE: &[usize] = &[1, 0];
+const ENEMIES: &[usize] = &[1, 1];
+const BOSS_BAR: &[usize] = &[1, 2];
+const EFFECTS: &[usize] = &[1, 3];
+const HEALTH: &[usize] = &[2, 0];
+const STAMINA: &[usize] = &[2, 1];
+const HOTBAR: &[usize] = &[2, 2];
+const MANA: &[usize] = &[2, 3];
+const AMMO: &[usize] = &[2, 4];
+const CENTER: &[usize] = &[3];
+
+fn replace(root: &mut GUIElement, path: &[usize], element: GUIElement) {
+	let mut current = root;
+	for &index in path {
+		current = match current.children.get_mut(index) {
+			Some(child) => child,
+			None => {
+				log::error!("no hud element at {:?}", path);
+				return;
+			},
+		};
+	}
+	*current = element;
+}
+
+impl Hud {
+	pub fn new(state: &mut State, window_id: ArenaId<Window>, camera_id: ArenaId<Camera>) -> Self {
+		let ui_id = state.guis.insert(camera_view(camera_id));
+		let window = state.windows.get_mut(&window_id).unwrap();
+		window.ui = Some(ui_id);
+
+		Self {
+			window_id,
+			camera_id,
+			ui_id,
+			shown: None,
+		}
+	}
+
+	/// The whole tree, only built for the first frame.
+	fn build(&self, hud: &HudState) -> GUIElement {
+		let hotbar: Vec<GUIElement> = hud.hotbar.iter().map(hotbar_cell).collect();
+
+		stack(&[
+			camera_view(self.camera_id),
+			row(&[
+				text(&format!("Wave {}", hud.wave)),
+				text(&format!("{} enemies left", hud.enemies_remaining)),
+				boss_bar(hud.boss_health),
+				effects_text(&hud.effects),
+			]).height(0.05).anchor_top(),
+			row(&[
+				bar(hud.health, Color::RED),
+				bar(hud.stamina, Color::GREEN),
+				row(&hotbar),
+				bar(hud.mana, Color::BLUE),
+				ammo_text(hud.ammo),
+			]).height(0.1).anchor_bottom(),
+			center(hud),
+		])
+	}
+
+	pub fn update(&mut self, state: &mut State, hud: HudState) {
+		let shown = match &self.shown {
+			Some(shown) => shown,
+			None => {
+				let ui = self.build(&hud);
+				if let Some(gui) = state.guis.get_mut(&self.ui_id) {
+					*gui = ui;
+				}
+				self.shown = Some(hud);
+				return;
+			},
+		};
+		let gui = match state.guis.get_mut(&self.ui_id) {
+			Some(gui) => gui,
+			None => return,
+		};
+
+		if hud.wave != shown.wave {
+			replace(gui, WAVE, text(&format!("Wave {}", hud.wave)));
+		}
+		if hud.enemies_remaining != shown.enemies_remaining {
+			replace(gui, ENEMIES, text(&format!("{} enemies left", hud.enemies_remaining)));
+		}
+		if hud.boss_health != shown.boss_health {
+			replace(gui, BOSS_BAR, boss_bar(hud.boss_health));
+		}
+		if hud.effects != shown.effects {
+			replace(gui, EFFECTS, effects_text(&hud.effects));
+		}
+		if hud.health != shown.health {
+			replace(gui, HEALTH, bar(hud.health, Color::RED));
+		}
+		if hud.stamina != shown.stamina {
+			replace(gui, STAMINA, bar(hud.stamina, Color::GREEN));
+		}
+		if hud.hotbar.len() != shown.hotbar.len() {
+			let hotbar: Vec<GUIElement> = hud.hotbar.iter().map(hotbar_cell).collect();
+			replace(gui, HOTBAR, row(&hotbar));
+		} else {
+			for (index, slot) in hud.hotbar.iter().enumerate() {
+				if *slot != shown.hotbar[index] {
+					replace(gui, &[HOTBAR, &[index]].concat(), hotbar_cell(slot));
+				}
+			}
+		}
+		if hud.mana != shown.mana {
+			replace(gui, MANA, bar(hud.mana, Color::BLUE));
+		}
+		if hud.ammo != shown.ammo {
+			replace(gui, AMMO, ammo_text(hud.ammo));
+		}
+		if hud.banner != shown.banner || hud.crosshair != shown.crosshair || hud.hit_marker != shown.hit_marker {
+			replace(gui, CENTER, center(&hud));
+		}
+
+		self.shown = Some(hud);
+	}
+
+	pub fn destroy(&mut self, state: &mut State) {
+		if let Some(window) = state.windows.get_mut(&self.window_id) {
+			window.ui = None;
+		}
+		state.guis.remove(&self.ui_id);
+	}
+}
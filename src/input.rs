@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use pge::KeyboardKey;
+use pge::MouseButton;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// What the player wants to do, independent of which key or button asked for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+	MoveForward,
+	MoveBackward,
+	MoveLeft,
+	MoveRight,
+	Jump,
+	Sprint,
+	Dash,
+	Crouch,
+	/// Toggles prone on and off.
+	Prone,
+	PrimaryFire,
+	SecondaryFire,
+	ThirdAction,
+	Reload,
+	ThrowGrenade,
+	Drop,
+	Grab,
+	Slot1,
+	Slot2,
+	Slot3,
+	Slot4,
+	Slot5,
+	Slot6,
+	SkipIntermission,
+	Restart,
+	IncreaseSensitivity,
+	DecreaseSensitivity,
+	ToggleInvertY,
+	CycleCamera,
+}
+
+impl Action {
+	/// Inventory slot selected by this action, if it is one of the slot actions.
+	pub fn slot(&self) -> Option<usize> {
+		match self {
+			Action::Slot1 => Some(0),
+			Action::Slot2 => Some(1),
+			Action::Slot3 => Some(2),
+			Action::Slot4 => Some(3),
+			Action::Slot5 => Some(4),
+			Action::Slot6 => Some(5),
+			_ => None,
+		}
+	}
+}
+
+/// Keys can be bound by their `KeyboardKey` variant name.
+fn parse_key(name: &str) -> Option<KeyboardKey> {
+	let key = match name {
+		"A" => KeyboardKey::A,
+		"B" => KeyboardKey::B,
+		"C" => KeyboardKey::C,
+		"D" => KeyboardKey::D,
+		"E" => KeyboardKey::E,
+		"F" => KeyboardKey::F,
+		"G" => KeyboardKey::G,
+		"H" => KeyboardKey::H,
+		"I" => KeyboardKey::I,
+		"J" => KeyboardKey::J,
+		"K" => KeyboardKey::K,
+		"L" => KeyboardKey::L,
+		"M" => KeyboardKey::M,
+		"N" => KeyboardKey::N,
+		"O" => KeyboardKey::O,
+		"P" => KeyboardKey::P,
+		"Q" => KeyboardKey::Q,
+		"R" => KeyboardKey::R,
+		"S" => KeyboardKey::S,
+		"T" => KeyboardKey::T,
+		"U" => KeyboardKey::U,
+		"V" => KeyboardKey::V,
+		"W" => KeyboardKey::W,
+		"X" => KeyboardKey::X,
+		"Y" => KeyboardKey::Y,
+		"Z" => KeyboardKey::Z,
+		"Digit0" => KeyboardKey::Digit0,
+		"Digit1" => KeyboardKey::Digit1,
+		"Digit2" => KeyboardKey::Digit2,
+		"Digit3" => KeyboardKey::Digit3,
+		"Digit4" => KeyboardKey::Digit4,
+		"Digit5" => KeyboardKey::Digit5,
+		"Digit6" => KeyboardKey::Digit6,
+		"Digit7" => KeyboardKey::Digit7,
+		"Digit8" => KeyboardKey::Digit8,
+		"Digit9" => KeyboardKey::Digit9,
+		"Space" => KeyboardKey::Space,
+		"Enter" => KeyboardKey::Enter,
+		"Escape" => KeyboardKey::Escape,
+		"Tab" => KeyboardKey::Tab,
+		"Backspace" => KeyboardKey::Backspace,
+		"ShiftLeft" => KeyboardKey::ShiftLeft,
+		"ShiftRight" => KeyboardKey::ShiftRight,
+		"ControlLeft" => KeyboardKey::ControlLeft,
+		"ControlRight" => KeyboardKey::ControlRight,
+		"AltLeft" => KeyboardKey::AltLeft,
+		"AltRight" => KeyboardKey::AltRight,
+		"Minus" => KeyboardKey::Minus,
+		"Equal" => KeyboardKey::Equal,
+		"ArrowUp" => KeyboardKey::ArrowUp,
+		"ArrowDown" => KeyboardKey::ArrowDown,
+		"ArrowLeft" => KeyboardKey::ArrowLeft,
+		"ArrowRight" => KeyboardKey::ArrowRight,
+		_ => return None,
+	};
+	Some(key)
+}
+
+/// Mouse buttons are bound with a `Mouse` prefix, e.g. `MouseLeft`.
+fn parse_button(name: &str) -> Option<MouseButton> {
+	match name.strip_prefix("Mouse")? {
+		"Left" => Some(MouseButton::Left),
+		"Right" => Some(MouseButton::Right),
+		"Middle" => Some(MouseButton::Middle),
+		_ => None,
+	}
+}
+
+/// Maps actions to input names. Keys use the `KeyboardKey` variant name (`W`, `ShiftLeft`,
+/// `Digit1`) and mouse buttons are prefixed with `Mouse` (`MouseLeft`). The same input may
+/// be bound to several actions, e.g. `R` both reloads and restarts after a game over.
+/// Names are parsed once when the bindings are loaded, unknown ones are logged and ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<Action, Vec<String>>", into = "BTreeMap<Action, Vec<String>>")]
+pub struct Bindings {
+	actions: BTreeMap<Action, Vec<String>>,
+	keys: Vec<(KeyboardKey, Action)>,
+	buttons: Vec<(MouseButton, Action)>,
+}
+
+impl From<BTreeMap<Action, Vec<String>>> for Bindings {
+	fn from(actions: BTreeMap<Action, Vec<String>>) -> Self {
+		let mut keys = Vec::new();
+		let mut buttons = Vec::new();
+		for (action, inputs) in &actions {
+			for input in inputs {
+				if let Some(key) = parse_key(input) {
+					keys.push((key, *action));
+				} else if let Some(button) = parse_button(input) {
+					buttons.push((button, *action));
+				} else {
+					log::error!("unknown input {} bound to {:?}", input, action);
+				}
+			}
+		}
+		Self { actions, keys, buttons }
+	}
+}
+
+impl From<Bindings> for BTreeMap<Action, Vec<String>> {
+	fn from(bindings: Bindings) -> Self {
+		bindings.actions
+	}
+}
+
+impl Default for Bindings {
+	fn default() -> Self {
+		let defaults: &[(Action, &str)] = &[
+			(Action::MoveForward, "W"),
+			(Action::MoveBackward, "S"),
+			(Action::MoveLeft, "A"),
+			(Action::MoveRight, "D"),
+			(Action::Jump, "Space"),
+			(Action::Sprint, "ShiftLeft"),
+			(Action::Dash, "Q"),
+			(Action::Crouch, "ControlLeft"),
+			(Action::Prone, "Z"),
+			(Action::PrimaryFire, "MouseLeft"),
+			(Action::SecondaryFire, "MouseRight"),
+			(Action::ThirdAction, "MouseMiddle"),
+			(Action::Reload, "R"),
+			(Action::ThrowGrenade, "T"),
+			(Action::Drop, "G"),
+			(Action::Grab, "F"),
+			(Action::Slot1, "Digit1"),
+			(Action::Slot2, "Digit2"),
+			(Action::Slot3, "Digit3"),
+			(Action::Slot4, "Digit4"),
+			(Action::Slot5, "Digit5"),
+			(Action::Slot6, "Digit6"),
+			(Action::SkipIntermission, "Enter"),
+			(Action::Restart, "R"),
+			(Action::IncreaseSensitivity, "Equal"),
+			(Action::DecreaseSensitivity, "Minus"),
+			(Action::ToggleInvertY, "I"),
+			(Action::CycleCamera, "V"),
+		];
+
+		let mut actions = BTreeMap::new();
+		for (action, input) in defaults {
+			actions.insert(*action, vec![input.to_string()]);
+		}
+		Self::from(actions)
+	}
+}
+
+impl Bindings {
+	/// Fills in the default binding for every action the player hasn't bound.
+	pub fn with_defaults(self) -> Self {
+		let mut actions = self.actions;
+		for (action, inputs) in Self::default().actions {
+			actions.entry(action).or_insert(inputs);
+		}
+		Self::from(actions)
+	}
+
+	pub fn key_actions(&self, key: KeyboardKey) -> Vec<Action> {
+		self.keys.iter().filter(|(k, _)| *k == key).map(|(_, action)| *action).collect()
+	}
+
+	pub fn mouse_actions(&self, button: MouseButton) -> Vec<Action> {
+		self.buttons.iter().filter(|(b, _)| *b == button).map(|(_, action)| *action).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_default_binding_parses() {
+		let bindings = Bindings::default();
+		assert_eq!(bindings.keys.len() + bindings.buttons.len(), bindings.actions.len());
+	}
+
+	#[test]
+	fn unknown_names_are_skipped() {
+		let mut actions = BTreeMap::new();
+		actions.insert(Action::Jump, vec!["Space".to_string(), "NotAKey".to_string()]);
+		let bindings = Bindings::from(actions);
+		assert_eq!(bindings.key_actions(KeyboardKey::Space), vec![Action::Jump]);
+		assert_eq!(bindings.keys.len(), 1);
+	}
+}
@@ -0,0 +1,48 @@
+use pge::*;
+
+use crate::utility::load_model;
+
+/// Shows a single glTF model, for checking assets outside of a game.
+pub struct ModelViewer {
+	path: String,
+}
+
+impl ModelViewer {
+	pub fn new(path: String) -> Self {
+		Self {
+			path,
+		}
+	}
+}
+
+impl App for ModelViewer {
+	fn on_create(&mut self, state: &mut State) {
+		let scene = Scene::new();
+		let scene_id = state.scenes.insert(scene);
+
+		let (model_node_id, _) = load_model(&self.path, state);
+		state.nodes.get_mut(&model_node_id).unwrap().parent = NodeParent::Scene(scene_id);
+		log::info!("inspecting {}", self.path);
+
+		let mut light_node = Node::new();
+		light_node.set_translation(5.0, 10.0, 5.0);
+		light_node.parent = NodeParent::Scene(scene_id);
+		let light_node_id = state.nodes.insert(light_node);
+		let mut light = PointLight::new();
+		light.node_id = Some(light_node_id);
+		state.point_lights.insert(light);
+
+		let mut eye = Node::new();
+		eye.set_translation(0.0, 3.0, 6.0);
+		eye.looking_at(0.0, 1.0, 0.0);
+		eye.parent = NodeParent::Scene(scene_id);
+		let eye_id = state.nodes.insert(eye);
+
+		let mut camera = Camera::new();
+		camera.node_id = Some(eye_id);
+		let camera_id = state.cameras.insert(camera);
+
+		let gui_id = state.guis.insert(camera_view(camera_id));
+		state.windows.insert(window().title(&self.path).ui(gui_id));
+	}
+}
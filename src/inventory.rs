@@ -1,9 +1,11 @@
 use pge::ArenaId;
 use pge::Node;
 use crate::types::Item;
+use crate::types::ItemContext;
 
 pub struct Inventory {
 	active: Option<usize>,
+	capacity: usize,
 	items: Vec<Box<dyn Item>>,
 }
 
@@ -11,12 +13,31 @@ impl Inventory{
 	pub fn new(size: usize) -> Self {
 		Self {
 			active: None,
+			capacity: size,
 			items: Vec::new(),
 		}
 	}
 
-	pub fn add_item<T: Item + 'static>(&mut self, item: T) {
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	pub fn active(&self) -> Option<usize> {
+		self.active
+	}
+
+	pub fn slot(&self, index: usize) -> Option<&dyn Item> {
+		self.items.get(index).map(|item| &**item)
+	}
+
+	/// Hands the item back when the inventory is full, whatever it loaded into the
+	/// state is still up to the caller.
+	pub fn add_item<T: Item + 'static>(&mut self, item: T) -> Result<(), T> {
+		if self.items.len() >= self.capacity {
+			return Err(item);
+		}
 		self.items.push(Box::new(item));
+		Ok(())
 	}
 
 	pub fn prepare(&mut self, state: &mut pge::State) {
@@ -26,22 +47,27 @@ impl Inventory{
 	}
 
 	pub fn equip(&mut self, index: usize, state: &mut pge::State, parent_id: ArenaId<Node>) {
+		if index >= self.items.len() {
+			log::info!("No item at index {}", index);
+			return;
+		}
+
 		if let Some(active) = self.active {
 			self.items[active].hide(state);
 		}
 
-		let item = match self.items.get_mut(index) {
-			Some(item) => item,
-			None => {
-				log::info!("No item at index {}", index);
-				return;
-			},
-		};
-
-		item.activate(state, parent_id);
+		self.items[index].activate(state, parent_id);
 		self.active = Some(index);
 	}
 
+	pub fn destroy(&mut self, state: &mut pge::State) {
+		for item in &mut self.items {
+			item.destroy(state);
+		}
+		self.items.clear();
+		self.active = None;
+	}
+
 	pub fn drop(&mut self, state: &mut pge::State) {
 		
 	}
@@ -53,15 +79,22 @@ impl Inventory{
 		}
 	}
 
+	pub fn current_item(&self) -> Option<&dyn Item> {
+		match self.active {
+			Some(index) => Some(&*self.items[index]),
+			None => None,
+		}
+	}
+
 	pub fn on_mouse_moved(&mut self, dx: f32, dy: f32, state: &mut pge::State) {
 		if let Some(active) = self.active {
 			self.items[active].on_mouse_moved(dx, dy, state);
 		}
 	}
 
-	pub fn process(&mut self, state: &mut pge::State, dt: f32) {
+	pub fn process(&mut self, ctx: &mut ItemContext, dt: f32) {
 		for item in &mut self.items {
-			item.process(state, dt);
+			item.process(ctx, dt);
 		}
 	}
 }
@@ -2,11 +2,19 @@ use std::f32::consts::PI;
 use pge::*;
 
 use crate::types::Item;
+use crate::types::ItemContext;
 use crate::utility::load_model;
+use crate::utility::unload_model;
+
+const SWING_REACH: f32 = 2.0;
+const SWING_RADIUS: f32 = 1.5;
+const SWING_DAMAGE: u32 = 40;
 
 pub struct Katana {
     node_id: ArenaId<Node>,
+    model_id: ArenaId<Model>,
     attacking: bool,
+    swing_pending: bool,
 
 	x_rotation: f32,
 	y_rotation: f32,
@@ -16,13 +24,15 @@ pub struct Katana {
 
 impl Katana {
     pub fn new(state: &mut State, scene_id: ArenaId<Scene>) -> Self {
-        let node_id = load_model("assets/katana.glb", state);
+        let (node_id, model_id) = load_model("assets/katana.glb", state);
         let node = state.nodes.get_mut(&node_id).unwrap();
         node.translation = Vec3::new(0.3, -1.0, 3.0);
         // node.rotation = Quat::from_euler(EulerRot::YXZ, 0.0, 0.0, 220.0_f32.to_radians());
         Self {
             node_id,
+            model_id,
             attacking: false,
+            swing_pending: false,
             x_rotation: 0.0,
 			y_rotation: 90.0_f32.to_radians(),
 			z_rotation: 0.0,
@@ -32,6 +42,10 @@ impl Katana {
 }
 
 impl Item for Katana {
+    fn name(&self) -> &str {
+        "Katana"
+    }
+
     fn prepare(&mut self, state: &mut pge::State) {}
 
     fn activate(&mut self, state: &mut pge::State, parent_id: ArenaId<Node>) {
@@ -44,9 +58,14 @@ impl Item for Katana {
         node.parent = NodeParent::Orphan;
     }
 
+    fn destroy(&mut self, state: &mut pge::State) {
+        unload_model(state, self.node_id, self.model_id);
+    }
+
     fn start_primary_action(&mut self, state: &mut State) {
         log::info!("start shooting");
         self.attacking = true;
+        self.swing_pending = true;
 		self.x_rotation = 0.0;
 		self.y_rotation = 180.0_f32.to_radians();
 		//self.y_rotation = 90.0_f32.to_radians();
@@ -62,11 +81,19 @@ impl Item for Katana {
 		self.z_rotation = 0.0;
     }
 
-    fn process(&mut self, state: &mut State, dt: f32) {
-        let node = state.nodes.get_mut(&self.node_id).unwrap();
+    fn process(&mut self, ctx: &mut ItemContext, dt: f32) {
+        let node = ctx.state.nodes.get_mut(&self.node_id).unwrap();
 		let target_rotation = Quat::from_euler(EulerRot::YXZ, self.x_rotation, self.y_rotation, self.z_rotation);
         let new_rotation = node.rotation.slerp(target_rotation, dt * self.rotation_speed);
         node.rotation = new_rotation;
+
+        if self.swing_pending {
+            self.swing_pending = false;
+            if let Some(owner) = ctx.state.nodes.get(&ctx.owner) {
+                let translation = owner.translation + owner.rotation * Vec3::new(0.0, 0.0, SWING_REACH);
+                ctx.combat.strike(ctx.owner, ctx.team, translation, SWING_RADIUS, SWING_DAMAGE);
+            }
+        }
     }
 
 	fn on_mouse_moved(&mut self, dx: f32, dy: f32, state: &mut State) {
@@ -1,3 +1,5 @@
-
+pub mod combat;
+pub mod effects;
 pub mod generated_pvp_map;
-pub mod types;
\ No newline at end of file
+pub mod types;
+pub mod utility;
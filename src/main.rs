@@ -13,364 +13,67 @@ mod dark_dungeon;
 mod controller;
 mod pvp;
 mod generated_pvp_map;
-
-use std::time::Instant;
-use log::LevelFilter;
+mod combat;
+mod boss;
+mod waves;
+mod score;
+mod hud;
+mod input;
+mod settings;
+mod movement;
+mod spells;
+mod effects;
+mod classes;
+mod inspect;
+
+use args::Args;
+use args::Command;
+use clap::Parser;
+use inspect::ModelViewer;
 use pge::*;
-use player::Player;
+use pvp::PVP;
+use score::HighScores;
+use settings::Settings;
 use survival::Survival;
-use utility::MoveDirection;
 
 enum GameMode {
 	Loading,
 	Survival(Survival),
-	PVP
-}
-
-struct Bullet {
-	spawned: Instant,
-	node_id: ArenaId<Node>,
+	PVP(PVP)
 }
 
 pub struct WizardWars {
-	main_scene: Option<ArenaId<Scene>>,
-	sensitivity: f32,
-	player_id: Option<ArenaId<Node>>,
-	yaw: f32,
-	pitch: f32,
-	speed: f32,
-	pressed_keys: MoveDirection,
-	dashing: bool,
-	movement_force: f32,
-	rng: rand::rngs::ThreadRng,
-	player_ray: Option<ArenaId<RayCast>>,
-	gripping: bool,
-	gripping_node: Option<ArenaId<Node>>,
-	bullets: Vec<Bullet>,
-	firing_rate: Instant,
-	shooting: bool,
-	bullet_mesh: Option<ArenaId<Mesh>>,
-	current_player: Option<Player>,
-	players: Vec<Player>,
 	game_mode: GameMode,
+	seed: Option<u64>,
+	settings: Settings,
+	pvp: bool,
 }
 
 impl WizardWars {
-	pub fn new() -> Self {
+	pub fn new(seed: Option<u64>, settings: Settings, pvp: bool) -> Self {
 		Self {
-			main_scene: None,
-			sensitivity: 0.001,
-			player_id: None,
-			yaw: 0.0,
-			pitch: 0.0,
-			speed: 10.0,
-			pressed_keys: MoveDirection::new(),
-			dashing: false,
-			movement_force: 1600.0,
-			rng: rand::thread_rng(),
-			player_ray: None,
-			gripping: false,
-			gripping_node: None,
-			bullets: Vec::new(),
-			firing_rate: Instant::now(),
-			shooting: false,
-			bullet_mesh: None,
-			current_player: None,
-			players: Vec::new(),
-			game_mode: GameMode::Loading
-		}
-	}
-
-	fn handle_rays(&mut self, state: &mut State) {
-		if !self.gripping {
-			return;
-		}
-
-		let player_ray = match self.player_ray {
-			Some(index) => match state.raycasts.get_mut(&index) {
-				Some(ray) => ray,
-				None => return,
-			},
-			None => return,
-		};
-
-		if player_ray.intersects.len() == 0 {
-			return;
-		}
-
-		let translation = {
-			let player_inx = match self.player_id {
-				Some(index) => index,
-				None => return,
-			};
-
-			let player = match state.nodes.get_mut(&player_inx) {
-				Some(node) => node,
-				None => return,
-			};
-
-			let dir = player.rotation * Vec3::new(0.0, 0.0, 1.0);
-			player.translation + dir * 5.0
-		};
-
-		let node = match self.gripping_node {
-			Some(index) => match state.nodes.get_mut(&index) {
-				Some(node) => node,
-				None => return,
-			},
-			None =>  {
-				match player_ray.intersects.first() {
-					Some(inx) => {
-						self.gripping_node = Some(*inx);
-						match state.nodes.get_mut(inx) {
-							Some(node) => node,
-							None => return,
-						}
-					},
-					None => return,
-				}
-			},
-		};
-
-		// let first_node = match player_ray.intersects.first() {
-		// 	Some(inx) => {
-		// 		self.gripping_node = Some(*inx);
-		// 		match state.nodes.get_mut(inx) {
-		// 			Some(node) => node,
-		// 			None => return,
-		// 		}
-		// 	},
-		// 	None => return,
-		// };
-
-		if node.physics.typ != PhycisObjectType::Dynamic {
-			return;
-		}
-
-		node.translation = translation;
-	}
-
-	fn handle_dashing(&mut self, state: &mut State) {
-		if self.dashing {
-			let player_inx = match self.player_id {
-				Some(index) => index,
-				None => return,
-			};
-			let player = match state.nodes.get_mut(&player_inx) {
-				Some(node) => node,
-				None => return,
-			};
-			let dir = player.rotation * Vec3::new(0.0, 0.0, 1.0);
-			player.physics.velocity = dir * 100.0;
-		}
-	}
-
-	fn handle_shooting(&mut self, state: &mut State) {
-		if self.firing_rate.elapsed().as_secs_f32() < 0.1 {
-			return;
-		}
-		self.firing_rate = Instant::now();
-
-		if !self.shooting {
-			return;
-		}
-
-		let player_inx = match self.player_id {
-			Some(index) => index,
-			None => return,
-		};
-
-		if let Some(bullet_mesh_id) = self.bullet_mesh {
-			log::info!("spawn bullet");
-			let mut bullet = Node::new();
-			bullet.mesh = Some(bullet_mesh_id);
-			bullet.physics.typ = PhycisObjectType::Dynamic;
-			bullet.physics.mass = 1.0;
-			bullet.collision_shape = Some(CollisionShape::Box { size: Vec3::new(0.3, 0.3, 0.3) });
-			bullet.parent = NodeParent::Scene(self.main_scene.unwrap());
-			let rotation = state.nodes.get(&player_inx).unwrap().rotation;
-			let mut translation = state.nodes.get(&player_inx).unwrap().translation;
-			// location in fron of player
-			translation += rotation * Vec3::new(0.0, 0.0, 3.0);
-			bullet.translation = translation;
-			
-			let dir = rotation * Vec3::new(0.0, 0.0, 1.0);
-			bullet.physics.velocity = dir * 50.0;
-			let bullet_id = state.nodes.insert(bullet);
-			self.bullets.push(Bullet {
-				spawned: Instant::now(),
-				node_id: bullet_id,
-			});
-		}
-
-		let player = match state.nodes.get_mut(&player_inx) {
-			Some(node) => node,
-			None => return,
-		};
-
-		// let dir = player.rotation * Vec3::new(0.0, 0.0, 0.3);
-		// self.recoil_force = dir * -100.0;
-
-		// rotate comera up
-		self.pitch -= 0.05;
-		// let rot = glam::Quat::from_euler(glam::EulerRot::YXZ, 0.0, 0.3, 0.0);
-		// player.rotation = rot * player.rotation;
-	}
-
-	fn handle_moving(&mut self, state: &mut State) {
-		let player = match self.player_id {
-			Some(index) => match state.nodes.get_mut(&index) {
-				Some(player) => player,
-				None => return,
-			},
-			None => return,
-		};
-
-		let current_speed = player.physics.velocity.length();
-		if self.pressed_keys.is_moving() {
-			let dir = self.pressed_keys.to_vec3();
-			let mut force = player.rotation * dir;
-
-			if force.x > 0.0 && player.physics.velocity.x < 0.0 {
-				force.x += -player.physics.velocity.x * self.movement_force;
-			} else if force.x < 0.0 && player.physics.velocity.x > 0.0 {
-				force.x += -player.physics.velocity.x * self.movement_force;
-			} else if current_speed < 25.0 {
-				force.x *= self.movement_force;
-			}
-
-			if force.z > 0.0 && player.physics.velocity.z < 0.0 {
-				force.z += -player.physics.velocity.z * self.movement_force;
-			} else if force.z < 0.0 && player.physics.velocity.z > 0.0 {
-				force.z += -player.physics.velocity.z * self.movement_force;
-			} else if current_speed < 25.0 {
-				force.z *= self.movement_force;
-			}
-
-			force.y = 0.0;
-
-			player.physics.force = force;
-			log::info!("force: {:?}", player.physics.force);
-		} else {
-			// We calculate force opposite of momevement to slow down the player
-			let force = -player.physics.velocity.xz() * self.movement_force;
-			player.physics.force = Vec3::new(force.x, 0.0, force.y);
-			//player.physics.force = glam::Vec3::ZERO;
-		}
-
-		if self.dashing {
-			let dir = player.rotation * Vec3::new(0.0, 0.0, 1.0);
-			player.physics.velocity = dir * 100.0;
+			game_mode: GameMode::Loading,
+			seed,
+			settings,
+			pvp,
 		}
 	}
-
-	pub fn rotate_player(&mut self, x: f32, y: f32) {
-		self.yaw += x * self.sensitivity;
-		self.pitch += y * self.sensitivity;
-		self.pitch = self.pitch.clamp(-1.5, 1.5);
-	}
-
-	pub fn hide_node(&mut self, state: &mut State, node_id: ArenaId<Node>) {
-		let node = match state.nodes.get_mut(&node_id) {
-			Some(node) => node,
-			None => return,
-		};
-
-		node.parent = NodeParent::Orphan;
-	}
 }
 
 impl pge::App for WizardWars {
 	fn on_create(&mut self, state: &mut pge::State) {
 		let window = Window::new()
 			.title("Wizard Wars")
-			.lock_cursor(true);
+			.size(self.settings.window.width, self.settings.window.height)
+			.fullscreen(self.settings.window.fullscreen)
+			.lock_cursor(self.settings.window.lock_cursor);
 		let window_id = state.windows.insert(window);
 
-		// let mut camera = pge::Camera::new();
-		// camera.zfar = 1000.0;
-		// camera.node_id = Some(player.node_id);
-		// let camera_id = state.cameras.insert(camera);
-
-		self.game_mode = GameMode::Survival(Survival::new(state, window_id));
-		// self.game_mode = GameMode::PVP(PVP::new(state, window_id));
-
-		let main_scene = Scene::new();
-		let main_scene_id = state.scenes.insert(main_scene);
-		self.main_scene = Some(main_scene_id);
-
-		// let player = Player::spawn(state);
-
-		let bullet_mesh = cube(0.3);
-		let bullet_mesh_id = state.meshes.insert(bullet_mesh);
-		self.bullet_mesh = Some(bullet_mesh_id);
-
-		// self.ak47 = Some(load_model("assets/ak47.glb", state));
-		// self.katana = Some(load_model("assets/katana.glb", state));
-
-		// self.inventory.prepare(state);
-
-		// let mut light_node = Node::new();
-		// light_node.translation = Vec3::new(0.0, 45.0, 0.0);
-		// let light_node_id = state.nodes.insert(light_node);
-
-
-
-		let mut player = Node::new();
-		player.translation = Vec3::new(0.0, 2.0, -30.0);
-		// player.looking_at(0.0, 0.0, 0.0);
-		player.physics.mass = 30.0;
-		player.physics.typ = PhycisObjectType::Dynamic;
-		player.collision_shape = Some(CollisionShape::Box { size: Vec3::new(1.0, 2.0, 1.0) });
-		player.parent = NodeParent::Scene(main_scene_id);
-		let player_id = state.nodes.insert(player);
-		self.player_id = Some(player_id);
-
-		let raycast = RayCast::new(player_id, 10.0);
-		let player_ray_inx = state.raycasts.insert(raycast);
-		self.player_ray = Some(player_ray_inx);
-
-		let mut camera = Camera::new();
-		camera.zfar = 1000.0;
-		camera.node_id = Some(player_id);
-		let camera_id = state.cameras.insert(camera);
-		// let gui = camera_view(camera_id);
-		// let gui_id = state.guis.insert(gui);
-
-		// let cube_mesh = cube(1.0);
-		// let cube_mesh_id = state.meshes.insert(cube_mesh);
-		
-
-		// // Generate cubes in random locations
-		// for i in 0..10 {
-		// 	let rand_x = self.rng.gen_range(-size / 2.0..size / 2.0);
-		// 	let rand_z = self.rng.gen_range(-size / 2.0..size / 2.0);
-
-		// 	let mut cube = Node::new();
-		// 	cube.mesh = Some(cube_mesh_id);
-		// 	cube.translation = Vec3::new(
-		// 		rand_x,
-		// 		10.0,
-		// 		rand_z,
-		// 	);
-		// 	cube.scale = Vec3::new(2.0, 2.0, 2.0);
-		// 	cube.collision_shape = Some(CollisionShape::Box { size: Vec3::new(2.0, 2.0, 2.0) });
-		// 	cube.physics.typ = PhycisObjectType::Dynamic;
-		// 	cube.physics.mass = 1.0;
-		// 	cube.parent = NodeParent::Scene(main_scene_id);
-		// 	let cube_id = state.nodes.insert(cube);
-		// }
-
-		let ui = stack(&[
-			camera_view(camera_id),
-			row(&[
-				rect().background_color(Color::BLUE),
-				rect().background_color(Color::RED),
-				rect().background_color(Color::CYAN),
-				rect().background_color(Color::WHITE)
-			]).height(0.1).anchor_bottom()
-		]);
-		let gui_id = state.guis.insert(ui);
+		self.game_mode = if self.pvp {
+			GameMode::PVP(PVP::new(state, window_id, self.seed, &self.settings))
+		} else {
+			GameMode::Survival(Survival::new(state, window_id, self.seed, self.settings.clone()))
+		};
 	}
 
 	fn on_keyboard_input(&mut self, window_id: ArenaId<Window>, key: KeyboardKey, action: KeyAction, state: &mut State) {
@@ -378,6 +81,9 @@ impl pge::App for WizardWars {
 			GameMode::Survival(ref mut survival) => {
 				survival.on_keyboard_input(key, action, state);
 			},
+			GameMode::PVP(ref mut pvp) => {
+				pvp.on_keyboard_input(key, action, state);
+			},
 			_ => {}
 		}
 	}
@@ -387,62 +93,11 @@ impl pge::App for WizardWars {
 			GameMode::Survival(ref mut survival) => {
 				survival.on_mouse_input(event, state);
 			},
+			GameMode::PVP(ref mut pvp) => {
+				pvp.on_mouse_input(event, state);
+			},
 			_ => {}
 		}
-
-		// match event {
-		// 	MouseEvent::Moved { dx, dy } => {
-		// 		let player_inx = match self.player_id {
-		// 			Some(index) => index,
-		// 			None => return,
-		// 		};
-		// 		self.rotate_player(dx, dy);
-		// 		let player = state.nodes.get_mut(&player_inx).unwrap();
-		// 		player.rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0);
-		// 	},
-		// 	MouseEvent::Pressed { button } => {
-		// 		match button {
-		// 			MouseButton::Left => {
-		// 				match self.gripping_node.take() {
-		// 					Some(gripping_node_id) => {
-		// 						self.gripping = false;
-		// 						let push_vel = {
-		// 							let player_inx = match self.player_id {
-		// 								Some(index) => index,
-		// 								None => return,
-		// 							};
-	
-		// 							let player = match state.nodes.get_mut(&player_inx) {
-		// 								Some(node) => node,
-		// 								None => return,
-		// 							};
-	
-		// 							let dir = player.rotation * Vec3::new(0.0, 0.0, 1.0);
-		// 							dir * 100.0
-		// 						};
-	
-		// 						if let Some(node) = state.nodes.get_mut(&gripping_node_id) {
-		// 							node.physics.velocity = push_vel;
-		// 						}
-		// 					},
-		// 					None => {
-		// 						self.shooting = true;
-		// 					}
-		// 				}
-						
-		// 				self.shooting = true
-		// 			},
-		// 			_ => {}
-		// 		}
-		// 	},
-		// 	MouseEvent::Released { button } => {
-		// 		match button {
-		// 			MouseButton::Left => self.shooting = false,
-		// 			_ => {}
-		// 		}
-		// 	},
-		// 	_ => {}
-		// }
 	}
 
 	fn on_process(&mut self, state: &mut State, delta: f32) {
@@ -450,46 +105,77 @@ impl pge::App for WizardWars {
 			GameMode::Survival(ref mut survival) => {
 				survival.on_process(state, delta);
 			},
+			GameMode::PVP(ref mut pvp) => {
+				pvp.on_process(state, delta);
+			},
 			_ => {}
 		};
+	}
+}
 
-		// if let Some(index) = self.light_inx {
-		// 	let light = state.nodes.get_mut(index).unwrap();
-		// 	self.light_circle_i += delta;
-		// 	let x = 10.0 * self.light_circle_i.cos();
-		// 	let z = 10.0 * self.light_circle_i.sin();
-		// 	light.set_translation(x, 10.0, z);
-		// }
+fn main() {
+    let args = Args::parse();
 
-		// self.handle_rays(state);
-		// self.handle_dashing(state);
-		// self.handle_shooting(state);
-		// self.handle_moving(state);
+    // Reported once logging is set up
+    let (mut settings, load_error) = match Settings::load() {
+        Ok(settings) => (settings, None),
+        Err(err) => (Settings::default(), Some(err)),
+    };
+    settings.apply_overrides(&args);
 
-		// for player in &mut self.players {
-		// 	player.process(state);
-		// }
-	}
+    pge::init_logging();
+    log::set_max_level(settings.log_level());
+    if let Some(err) = load_error {
+        log::warn!("failed to load settings from {}, using defaults and leaving the file alone: {}", Settings::path().display(), err);
+    }
+    log::info!("audio volume {:.0}%", settings.audio.volume * 100.0);
+
+    match args.command {
+        Some(Command::Pvp) => pge::run(WizardWars::new(args.seed, settings, true)).unwrap(),
+        Some(Command::Inspect { path }) => pge::run(ModelViewer::new(path)).unwrap(),
+        Some(Command::Scores { map, seed }) => print_scores(map, seed),
+        None => pge::run(WizardWars::new(args.seed, settings, false)).unwrap(),
+    }
 }
 
-fn main() {
-    let filter_level = match std::env::var("WIZARDWARS_LOG_LEVEL") {
-        Ok(lev) => {
-            match lev.as_str() {
-                "info" => LevelFilter::Info,
-                "debug" => LevelFilter::Debug,
-                "error" => LevelFilter::Error,
-                _ => LevelFilter::Info
-            }
-        },
-        Err(_) => {
-            LevelFilter::Info
+fn print_scores(map: Option<String>, seed: Option<u64>) {
+    let high_scores = match HighScores::load() {
+        Ok(high_scores) => high_scores,
+        Err(err) => {
+            println!("Failed to load high scores from {}: {}", HighScores::path().display(), err);
+            return;
         }
     };
 
-    pge::init_logging();
+    let mut tables: Vec<(String, Option<u64>)> = Vec::new();
+    for entry in &high_scores.entries {
+        let key = (entry.map.clone(), entry.seed);
+        if !tables.contains(&key) {
+            tables.push(key);
+        }
+    }
 
-    // let args = Args::parse();
+    for (table_map, table_seed) in tables {
+        if map.as_ref().map_or(false, |m| *m != table_map) || seed.map_or(false, |s| Some(s) != table_seed) {
+            continue;
+        }
 
-   	pge::run(WizardWars::new()).unwrap();
+        match table_seed {
+            Some(table_seed) => println!("{} (seed {})", table_map, table_seed),
+            None => println!("{} (random maps)", table_map),
+        }
+        for (i, entry) in high_scores.table(&table_map, table_seed).iter().enumerate() {
+            let seconds = entry.time_survived as u32;
+            println!(
+                "  {:>2}. {:>8}  wave {:>3}  {:>4} kills  {}:{:02}  {:.0}%",
+                i + 1,
+                entry.score,
+                entry.wave,
+                entry.kills,
+                seconds / 60,
+                seconds % 60,
+                entry.accuracy * 100.0
+            );
+        }
+    }
 }
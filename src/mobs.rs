@@ -1,54 +1,79 @@
 use pge::ArenaId;
-use pge::Node;
-use pge::NodeParent;
 use pge::Scene;
 use pge::State;
 use pge::Vec3;
 
-use crate::inventory::Inventory;
+use crate::movement::MovementParams;
 use crate::npc::Npc;
-use crate::player;
-use crate::utility::load_model;
+use crate::player::PlayerBuilder;
+use crate::types::MobArchetype;
+use crate::types::SummonRequest;
 
-// pub fn spawn_mob(state: &mut State, main_scene_id: ArenaId<Scene>, location: Vec3) -> Npc {
-// 	let path 
-
-// 	let player = PlayerBuilder::new(main_scene_id).build(state);
-// 	Npc::new(player)
-// }
+const MOB_MODEL: &str = "assets/orkki.glb";
+const MOB_TEAM: u32 = 1;
+const MOB_SIZE: Vec3 = Vec3::new(1.5, 2.0, 1.5);
+/// Minions are shrunk so they can be told apart from the mobs they fight.
+const MINION_SCALE: f32 = 0.7;
 
 pub struct MobSpawner {
 	main_scene_id: ArenaId<Scene>,
+	movement: MovementParams,
 	// model_node: ArenaId<Node>
 }
 
 impl MobSpawner {
-	pub fn new(state: &mut State, main_scene_id: ArenaId<Scene>) -> Self {
+	pub fn new(state: &mut State, main_scene_id: ArenaId<Scene>, movement: MovementParams) -> Self {
 		
 
 		Self {
 			main_scene_id,
+			movement,
 			// model_node: node_id
 		}
 	}
 
-	pub fn spawn(&mut self, state: &mut State, translation: Vec3) -> Npc {
-		// let cube = cube(1.5);
-		// let mesh_id = state.meshes.insert(cube);
-
-		let mut player_node = Node::new();
-		player_node.parent = NodeParent::Scene(self.main_scene_id);
-		player_node.translation = translation;
-		player_node.physics.mass = 10.0;
-		player_node.physics.typ = pge::PhycisObjectType::Dynamic;
-		player_node.collision_shape = Some(pge::CollisionShape::Box { size: Vec3::new(1.5, 2.0, 1.5) });
-		let player_node_id = state.nodes.insert(player_node);
-		let inventory = Inventory::new(4);
-		let player = player::Player::new(player_node_id, inventory);
-
-		let model_node_id = load_model("assets/orkki.glb", state);
-		let node = state.nodes.get_mut(&model_node_id).unwrap();
-		node.parent = NodeParent::Node(player.node_id);
-		Npc::new(player)
+	pub fn spawn(&mut self, state: &mut State, translation: Vec3, archetype: MobArchetype) -> Npc {
+		let player = self.mob(translation, MOB_TEAM).build(state);
+		Npc::new(player, archetype)
+	}
+
+	/// Spawns a temporary mob fighting for whoever summoned it.
+	pub fn spawn_minion(&mut self, state: &mut State, request: &SummonRequest) -> Npc {
+		let player = self.mob(request.translation, request.team)
+			.size(MOB_SIZE * MINION_SCALE)
+			.model(MOB_MODEL, MINION_SCALE)
+			.build(state);
+		let mut npc = Npc::new(player, request.archetype);
+		npc.summoner = Some(request.summoner);
+		npc.lifetime = Some(request.lifetime);
+		npc
+	}
+
+	pub fn spawn_boss(&mut self, state: &mut State, translation: Vec3) -> Npc {
+		let player = self.body(translation, MOB_TEAM)
+			.size(Vec3::new(4.0, 5.0, 4.0))
+			.mass(200.0)
+			.health(1000)
+			.max_speed(4.8)
+			.model(MOB_MODEL, 2.5)
+			.build(state);
+		Npc::new(player, MobArchetype::Melee)
 	}
-}
\ No newline at end of file
+
+	fn body(&self, translation: Vec3, team: u32) -> PlayerBuilder {
+		PlayerBuilder::new(self.main_scene_id)
+			.translation(translation)
+			.team(team)
+			.inventory_size(4)
+			.movement(self.movement.clone())
+	}
+
+	/// A regular sized mob.
+	fn mob(&self, translation: Vec3, team: u32) -> PlayerBuilder {
+		self.body(translation, team)
+			.size(MOB_SIZE)
+			.mass(10.0)
+			.max_speed(6.0)
+			.model(MOB_MODEL, 1.0)
+	}
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use pge::Vec3;
+use serde::Deserialize;
+
+/// Tunables for walking characters, shared by players and mobs. Speeds are in units per
+/// second and accelerations in units per second squared.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MovementParams {
+	pub max_speed: f32,
+	pub ground_acceleration: f32,
+	/// Deceleration on the ground when there is no input.
+	pub friction: f32,
+	pub air_acceleration: f32,
+	/// Steepest ground in degrees that still counts as standing on it.
+	pub slope_limit: f32,
+}
+
+impl Default for MovementParams {
+	fn default() -> Self {
+		Self {
+			max_speed: 25.0,
+			ground_acceleration: 120.0,
+			friction: 80.0,
+			air_acceleration: 20.0,
+			slope_limit: 45.0,
+		}
+	}
+}
+
+impl MovementParams {
+	pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+		let content = std::fs::read_to_string(path)?;
+		Self::parse(&content)
+	}
+
+	pub fn parse(content: &str) -> anyhow::Result<Self> {
+		let params: MovementParams = toml::from_str(content)?;
+		if params.max_speed <= 0.0 || params.ground_acceleration <= 0.0 || params.friction <= 0.0 {
+			anyhow::bail!("max_speed, ground_acceleration and friction must be positive");
+		}
+		Ok(params)
+	}
+
+	/// Whether ground with this normal can be stood on.
+	pub fn walkable(&self, normal: Vec3) -> bool {
+		normal.angle_between(Vec3::Y) <= self.slope_limit.to_radians()
+	}
+
+	/// Steps the horizontal velocity one frame towards moving along `wish_dir` at top speed.
+	/// `wish_dir` is a horizontal unit vector, or zero to stop. `speed_multiplier` scales
+	/// the top speed for sprinting and crouching. The vertical velocity is left alone.
+	pub fn step(&self, velocity: Vec3, wish_dir: Vec3, speed_multiplier: f32, grounded: bool, dt: f32) -> Vec3 {
+		let horizontal = Vec3::new(velocity.x, 0.0, velocity.z);
+
+		let horizontal = if wish_dir == Vec3::ZERO {
+			if grounded {
+				let speed = horizontal.length();
+				let slowed = (speed - self.friction * dt).max(0.0);
+				horizontal.normalize_or_zero() * slowed
+			} else {
+				// Keep momentum in the air
+				horizontal
+			}
+		} else {
+			let acceleration = if grounded { self.ground_acceleration } else { self.air_acceleration };
+			let target = wish_dir * self.max_speed * speed_multiplier;
+			horizontal + (target - horizontal).clamp_length_max(acceleration * dt)
+		};
+
+		Vec3::new(horizontal.x, velocity.y, horizontal.z)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const DT: f32 = 1.0 / 60.0;
+
+	#[test]
+	fn shipped_params_parse() {
+		MovementParams::load(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/movement.toml")).unwrap();
+	}
+
+	#[test]
+	fn reaches_top_speed_from_standstill() {
+		let params = MovementParams::default();
+		let mut velocity = Vec3::ZERO;
+		let mut time = 0.0;
+		while velocity.length() < params.max_speed {
+			velocity = params.step(velocity, Vec3::X, 1.0, true, DT);
+			time += DT;
+			assert!(time < 1.0, "never reached top speed");
+		}
+		// 25 / 120 seconds, rounded up to whole frames
+		assert!((time - 0.2167).abs() < 0.01, "took {}s", time);
+		assert_eq!(velocity, Vec3::new(params.max_speed, 0.0, 0.0));
+	}
+
+	#[test]
+	fn stops_from_top_speed() {
+		let params = MovementParams::default();
+		let mut velocity = Vec3::new(params.max_speed, 0.0, 0.0);
+		let mut distance = 0.0;
+		while velocity.length() > 0.0 {
+			velocity = params.step(velocity, Vec3::ZERO, 1.0, true, DT);
+			distance += velocity.x * DT;
+			assert!(distance < 10.0, "never stopped");
+		}
+		assert!((distance - 3.7).abs() < 0.01, "slid {}", distance);
+	}
+
+	#[test]
+	fn keeps_momentum_in_the_air() {
+		let params = MovementParams::default();
+		let velocity = Vec3::new(10.0, -3.0, 0.0);
+		assert_eq!(params.step(velocity, Vec3::ZERO, 1.0, false, DT), velocity);
+	}
+
+	#[test]
+	fn only_gentle_slopes_are_walkable() {
+		let params = MovementParams::default();
+		assert!(params.walkable(Vec3::Y));
+		assert!(params.walkable(Vec3::new(0.0, 1.0, 0.5).normalize()));
+		assert!(!params.walkable(Vec3::new(0.0, 1.0, 1.5).normalize()));
+		assert!(!params.walkable(Vec3::X));
+	}
+
+	#[test]
+	fn rejects_non_positive_speed() {
+		assert!(MovementParams::parse("max_speed = 0.0").is_err());
+	}
+}
@@ -1,31 +1,115 @@
+use pge::ArenaId;
+use pge::Node;
 use pge::Quat;
+use pge::Vec3;
 
+use crate::combat::Combat;
+use crate::combat::Target;
 use crate::player;
 use crate::player::Player;
+use crate::types::MobArchetype;
+use crate::utility::has_line_of_sight;
 
+const MELEE_RANGE: f32 = 2.5;
+const MELEE_ATTACK_INTERVAL: f32 = 1.0;
+const MELEE_DAMAGE: u32 = 10;
+const RANGED_MIN_DISTANCE: f32 = 10.0;
+const RANGED_MAX_DISTANCE: f32 = 20.0;
+const RANGED_ATTACK_RANGE: f32 = 30.0;
+const RANGED_ATTACK_INTERVAL: f32 = 1.5;
+const RANGED_PROJECTILE_SPEED: f32 = 40.0;
+const RANGED_PROJECTILE_DAMAGE: u32 = 10;
 
 pub struct Npc {
-	pub player: Player
+	pub player: Player,
+	pub archetype: MobArchetype,
+	/// Node of whoever summoned this mob, they get the credit for its kills.
+	pub summoner: Option<ArenaId<Node>>,
+	/// Seconds left for a temporary mob, None for one that stays until killed.
+	pub lifetime: Option<f32>,
+	since_last_attack: f32,
 }
 
 impl Npc {
-	pub fn new(player: Player) -> Self {
+	pub fn new(player: Player, archetype: MobArchetype) -> Self {
 		Self {
-			player
+			player,
+			archetype,
+			summoner: None,
+			lifetime: None,
+			since_last_attack: 0.0,
 		}
 	}
 
-	pub fn process(&mut self, state: &mut pge::State, enemy: &Player, dt: f32) {
-		
+	/// Whether a temporary mob has run out of time.
+	pub fn expired(&self) -> bool {
+		self.lifetime.map_or(false, |lifetime| lifetime <= 0.0)
+	}
+
+	/// Closest target that isn't on this mob's team.
+	fn closest_hostile(&self, state: &pge::State, targets: &[Target]) -> Option<Vec3> {
+		let origin = state.nodes.get(&self.player.node_id)?.translation;
+		targets
+			.iter()
+			.filter(|target| target.team != self.player.team)
+			.filter_map(|target| state.nodes.get(&target.node_id).map(|node| node.translation))
+			.min_by(|a, b| a.distance(origin).total_cmp(&b.distance(origin)))
+	}
+
+	pub fn process(&mut self, state: &mut pge::State, targets: &[Target], combat: &mut Combat, dt: f32) {
+
 		// let node = state.nodes.get_mut(&self.player.node_id).unwrap();
 		// self.player.rotate(10.0, 0.0);
-		// 
+		//
+
+		self.since_last_attack += dt;
+		if let Some(lifetime) = &mut self.lifetime {
+			*lifetime -= dt;
+		}
 
-		let translation = state.nodes.get(&enemy.node_id).unwrap().translation;
+		self.player.steering = None;
+		let translation = match self.closest_hostile(state, targets) {
+			Some(translation) => translation,
+			None => {
+				self.player.process(state, combat, dt);
+				return;
+			}
+		};
 		let player_node = state.nodes.get_mut(&self.player.node_id).unwrap();
 		player_node.looking_at(translation.x, translation.y, translation.z);
-		let dir = translation - player_node.translation;
-		player_node.translation += dir.normalize() * 0.1;
-		self.player.process(state, dt);
+		let origin = player_node.translation;
+		let distance = origin.distance(translation);
+		let dir = (translation - origin).normalize_or_zero();
+
+		// Movement is held back by the player, attacks have to be held back here
+		let can_act = self.player.effects.can_act();
+		match self.archetype {
+			MobArchetype::Melee => {
+				if distance > MELEE_RANGE {
+					self.player.steering = Some(dir);
+				} else if can_act && self.since_last_attack >= MELEE_ATTACK_INTERVAL {
+					self.since_last_attack = 0.0;
+					combat.strike(self.player.node_id, self.player.team, translation, 1.0, MELEE_DAMAGE);
+				}
+			},
+			MobArchetype::Ranged => {
+				if distance > RANGED_MAX_DISTANCE {
+					self.player.steering = Some(dir);
+				} else if distance < RANGED_MIN_DISTANCE {
+					self.player.steering = Some(-dir);
+				}
+
+				if can_act
+					&& distance <= RANGED_ATTACK_RANGE
+					&& self.since_last_attack >= RANGED_ATTACK_INTERVAL
+					&& has_line_of_sight(state, combat.scene_id(), origin, translation)
+				{
+					self.since_last_attack = 0.0;
+					combat.fire(state, self.player.node_id, self.player.team, origin + dir * 2.0, dir, RANGED_PROJECTILE_SPEED, RANGED_PROJECTILE_DAMAGE);
+				}
+			},
+		}
+
+		self.player.process(state, combat, dt);
 	}
-}
\ No newline at end of file
+}
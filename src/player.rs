@@ -1,120 +1,311 @@
 use pge::*;
+use crate::classes::WizardClass;
+use crate::combat::AreaEffect;
+use crate::combat::Combat;
+use crate::combat::Falloff;
+use crate::combat::Hit;
+use crate::combat::Shield;
+use crate::effects::StatusEffects;
 use crate::inventory::Inventory;
+use crate::movement::MovementParams;
+use crate::settings::CameraSettings;
+use crate::types::ItemContext;
+use crate::types::SummonRequest;
+use crate::utility::cast_ray_hit;
+use crate::utility::despawn_node;
+use crate::utility::load_model;
+use crate::utility::overlaps_static;
+use crate::utility::unload_model;
 use crate::utility::MoveDirection;
+use crate::utility::OVERLAP_SKIN;
+
+/// Creates the node, collider, model and inventory of a player or mob so every spawn
+/// path ends up with the same kind of body. Only players someone looks through need a
+/// `camera`.
+pub struct PlayerBuilder {
+	scene_id: ArenaId<Scene>,
+	translation: Vec3,
+	/// Collision box while standing.
+	size: Vec3,
+	mass: f32,
+	team: u32,
+	inventory_size: usize,
+	class: Option<WizardClass>,
+	health: Option<u32>,
+	movement: MovementParams,
+	max_speed: Option<f32>,
+	/// glTF model attached to the body and its scale.
+	model: Option<(&'static str, f32)>,
+	camera: Option<CameraSettings>,
+}
+
+impl PlayerBuilder {
+	pub fn new(scene_id: ArenaId<Scene>) -> Self {
+		Self {
+			scene_id,
+			translation: Vec3::ZERO,
+			size: Vec3::new(0.5, Stance::Standing.height(), 0.5),
+			mass: 100.0,
+			team: 0,
+			inventory_size: 6,
+			class: None,
+			health: None,
+			movement: MovementParams::default(),
+			max_speed: None,
+			model: None,
+			camera: None,
+		}
+	}
+
+	pub fn translation(mut self, translation: Vec3) -> Self {
+		self.translation = translation;
+		self
+	}
+
+	pub fn size(mut self, size: Vec3) -> Self {
+		self.size = size;
+		self
+	}
+
+	pub fn mass(mut self, mass: f32) -> Self {
+		self.mass = mass;
+		self
+	}
+
+	pub fn team(mut self, team: u32) -> Self {
+		self.team = team;
+		self
+	}
+
+	pub fn inventory_size(mut self, size: usize) -> Self {
+		self.inventory_size = size;
+		self
+	}
+
+	/// Stats and loadout of the class, `health` and `max_speed` still take precedence.
+	pub fn class(mut self, class: WizardClass) -> Self {
+		self.class = Some(class);
+		self
+	}
+
+	pub fn health(mut self, health: u32) -> Self {
+		self.health = Some(health);
+		self
+	}
+
+	pub fn movement(mut self, movement: MovementParams) -> Self {
+		self.movement = movement;
+		self
+	}
+
+	pub fn max_speed(mut self, max_speed: f32) -> Self {
+		self.max_speed = Some(max_speed);
+		self
+	}
+
+	pub fn model(mut self, path: &'static str, scale: f32) -> Self {
+		self.model = Some((path, scale));
+		self
+	}
+
+	/// First person camera looking out of the body, for players driven by a
+	/// `PlayerController`.
+	pub fn camera(mut self, settings: &CameraSettings) -> Self {
+		self.camera = Some(settings.clone());
+		self
+	}
 
-// pub struct PlayerBuilder {
-// 	scene_id: ArenaId<Scene>,
-// 	inventory_size: usize,
-// 	team: u32,
-// 	mana: u32,
-// 	health: u32,
-// 	mass: f32,
-// 	translation: Vec3,
-// }
-
-// impl PlayerBuilder {
-// 	pub fn new(<scene_id>: ArenaId<Scene>) -> Self {
-// 		Self {
-// 			scene_id,
-// 			inventory_size: 10,
-// 			team: 0,
-// 			mana: 100,
-// 			health: 100,
-// 			mass: 45.0,
-// 			translation: Vec3::ZERO,
-// 		}
-// 	}
-
-// 	pub fn team(mut self, team: u32) -> Self {
-// 		self.team = team;
-// 		self
-// 	}
-
-// 	pub fn inventory_size(mut self, size: usize) -> Self {
-// 		self.inventory_size = size;
-// 		self
-// 	}
-
-// 	pub fn mana(mut self, mana: u32) -> Self {
-// 		self.mana = mana;
-// 		self
-// 	}
-
-// 	pub fn health(mut self, health: u32) -> Self {
-// 		self.health = health;
-// 		self
-// 	}
-
-// 	pub fn mass(mut self, mass: f32) -> Self {
-// 		self.mass = mass;
-// 		self
-// 	}
-
-// 	pub fn translation(mut self, translation: Vec3) -> Self {
-// 		self.translation = translation;
-// 		self
-// 	}
-
-// 	pub fn build(self, state: &mut State) -> Player {
-// 		let mut node = Node::new();
-// 		node.physics.typ = PhycisObjectType::Dynamic;
-// 		node.physics.mass = self.mass;
-// 		node.collision_shape = Some(CollisionShape::Capsule {
-// 			radius: 0.5,
-// 			height: 1.8
-// 		});
-// 		node.translation = self.translation;
-// 		node.parent = NodeParent::Scene(self.scene_id);
-// 		let node_id = state.nodes.insert(node);
-
-// 		Player {
-// 			node_id,
-// 			crouching: false,
-// 			inventory: Inventory::new(self.inventory_size),
-// 			spriting: false,
-// 			jumping: false,
-// 			prone: false,
-// 			mana: 100,
-// 			team: self.team,
-// 			death: false,
-// 			// yaw: 0.0,
-// 			// pitch: 0.0,
-// 			movdir: MoveDirection::new(),
-// 			movement_force: 1600.0,
-// 		}
-// 	}
-// }
+	pub fn build(self, state: &mut State) -> Player {
+		let mut node = Node::new();
+		node.parent = NodeParent::Scene(self.scene_id);
+		node.translation = self.translation;
+		node.physics.typ = PhycisObjectType::Dynamic;
+		node.physics.mass = self.mass;
+		node.collision_shape = Some(CollisionShape::Box { size: self.size });
+		let node_id = state.nodes.insert(node);
+
+		let mut player = Player::new(node_id, Inventory::new(self.inventory_size));
+		player.team = self.team;
+		player.movement = self.movement;
+		if let Some(class) = self.class {
+			class.apply(state, self.scene_id, &mut player);
+		}
+		if let Some(health) = self.health {
+			player.health = health;
+			player.max_health = health;
+		}
+		if let Some(max_speed) = self.max_speed {
+			player.movement.max_speed = max_speed;
+		}
+
+		if let Some((path, scale)) = self.model {
+			let (model_node_id, model_id) = load_model(path, state);
+			let model_node = state.nodes.get_mut(&model_node_id).unwrap();
+			model_node.parent = NodeParent::Node(node_id);
+			model_node.scale = Vec3::new(scale, scale, scale);
+			player.model = Some((model_node_id, model_id));
+		}
+
+		if let Some(settings) = self.camera {
+			let mut camera = Camera::new();
+			camera.zfar = settings.view_distance;
+			camera.fovy = settings.fov.to_radians();
+			camera.node_id = Some(node_id);
+			player.camera_id = Some(state.cameras.insert(camera));
+		}
+
+		player
+	}
+}
+
+const JUMP_VELOCITY: f32 = 10.0;
+/// Seconds after walking off a ledge during which a jump still counts.
+const COYOTE_TIME: f32 = 0.1;
+/// Seconds a jump pressed in the air is remembered for the landing.
+const JUMP_BUFFER: f32 = 0.15;
+/// How far below the feet the ground may be for the player to count as standing on it.
+const GROUND_TOLERANCE: f32 = 0.1;
+const GRENADE_THROW_SPEED: f32 = 20.0;
+const GRENADE_FUSE: f32 = 2.5;
+const GRENADE: AreaEffect = AreaEffect {
+	radius: 6.0,
+	damage: 80,
+	falloff: Falloff::Linear,
+	impulse: 500.0,
+	status: None,
+};
+const SPRINT_MULTIPLIER: f32 = 1.6;
+/// Stamina drained per second of sprinting.
+const SPRINT_DRAIN: f32 = 20.0;
+/// Stamina recovered per second while not sprinting.
+const STAMINA_RECOVERY: f32 = 15.0;
+/// Seconds after sprinting or dashing before stamina starts to recover.
+const STAMINA_RECOVERY_DELAY: f32 = 0.8;
+/// Stamina needed to sprint again after running out.
+const SPRINT_RESUME_STAMINA: f32 = 25.0;
+const DASH_SPEED: f32 = 40.0;
+const DASH_COST: f32 = 25.0;
+const DASH_COOLDOWN: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stance {
+	Standing,
+	Crouching,
+	Prone,
+}
+
+impl Stance {
+	/// Height of the collision box.
+	pub fn height(&self) -> f32 {
+		match self {
+			Stance::Standing => 1.8,
+			Stance::Crouching => 1.2,
+			Stance::Prone => 0.5,
+		}
+	}
+
+	pub fn speed_multiplier(&self) -> f32 {
+		match self {
+			Stance::Standing => 1.0,
+			Stance::Crouching => 0.5,
+			Stance::Prone => 0.25,
+		}
+	}
+
+	pub fn spread_multiplier(&self) -> f32 {
+		match self {
+			Stance::Standing => 1.0,
+			Stance::Crouching => 0.6,
+			Stance::Prone => 0.3,
+		}
+	}
+}
 
 pub struct Player {
 	pub node_id: ArenaId<Node>,
-	mana: u32,
+	pub health: u32,
+	pub max_health: u32,
+	pub mana: f32,
+	pub max_mana: f32,
+	/// Mana recovered per second.
+	pub mana_regen: f32,
+	pub shield: Option<Shield>,
+	pub effects: StatusEffects,
+	/// Minions summoned by spells, waiting for the game mode to spawn them.
+	pub summons: Vec<SummonRequest>,
+	pub stamina: f32,
+	pub max_stamina: f32,
+	/// Seconds until stamina starts to recover.
+	stamina_delay: f32,
+	/// Set when stamina runs out, sprinting is blocked until it is back to
+	/// `SPRINT_RESUME_STAMINA`.
+	exhausted: bool,
+	/// Seconds until the next dash.
+	dash_cooldown: f32,
+	dash_requested: bool,
+	pub grenades: u32,
+	grenade_requested: bool,
 	pub inventory: Inventory,
 	spriting: bool,
-	pub jumping: bool,
+	/// Set by a jump until the player lands again.
+	jumping: bool,
+	grounded: bool,
+	since_grounded: f32,
+	/// Time left on a buffered jump request.
+	jump_buffer: f32,
+	/// Crouch and prone as asked for by input, `stance` follows once there is room.
 	crouching: bool,
 	prone: bool,
-	team: u32,
+	stance: Stance,
+	pub team: u32,
 	// yaw: f32,
 	// pitch: f32,
 	pub death: bool,
 	pub movdir: MoveDirection,
-	movement_force: f32,
+	pub movement: MovementParams,
+	/// World space direction to walk in, used by mobs instead of `movdir`.
+	pub steering: Option<Vec3>,
+	/// Model attached to the body by `PlayerBuilder`.
+	model: Option<(ArenaId<Node>, ArenaId<Model>)>,
+	pub camera_id: Option<ArenaId<Camera>>,
 }
 
 impl Player {
 	pub fn new(node_id: ArenaId<Node>, inventory: Inventory) -> Self {
 		Self {
 			node_id,
-			mana: 100,
+			health: 100,
+			max_health: 100,
+			mana: 100.0,
+			max_mana: 100.0,
+			mana_regen: 5.0,
+			shield: None,
+			effects: StatusEffects::default(),
+			summons: Vec::new(),
+			stamina: 100.0,
+			max_stamina: 100.0,
+			stamina_delay: 0.0,
+			exhausted: false,
+			dash_cooldown: 0.0,
+			dash_requested: false,
+			grenades: 3,
+			grenade_requested: false,
 			inventory,
 			spriting: false,
 			jumping: false,
+			grounded: false,
+			since_grounded: 0.0,
+			jump_buffer: 0.0,
 			crouching: false,
 			prone: false,
+			stance: Stance::Standing,
 			team: 0,
 			death: false,
 			movdir: MoveDirection::new(),
-			movement_force: 1600.0,
+			movement: MovementParams::default(),
+			steering: None,
+			model: None,
+			camera_id: None,
 		}
 	}
 
@@ -138,53 +329,140 @@ impl Player {
 		self.inventory.on_mouse_moved(dx, dy, state);
 	}
 
-	pub fn process(&mut self, state: &mut State, dt: f32) {
-		self.inventory.process(state, dt);
-
-		let node = state.nodes.get_mut(&self.node_id).unwrap();
-		// node.rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0);
-
-		let current_speed = node.physics.velocity.length();
-		if self.movdir.is_moving() {
-			let dir = self.movdir.to_vec3();
-			let mut force = node.rotation * dir;
-
-			if force.x > 0.0 && node.physics.velocity.x < 0.0 {
-				force.x += -node.physics.velocity.x * self.movement_force;
-			} else if force.x < 0.0 && node.physics.velocity.x > 0.0 {
-				force.x += -node.physics.velocity.x * self.movement_force;
-			} else if current_speed < 25.0 {
-				force.x *= self.movement_force;
+	/// Applies the damage and status effect of a hit. Returns the health lost like
+	/// `take_damage`.
+	pub fn take_hit(&mut self, hit: &Hit) -> u32 {
+		if let Some(status) = hit.status {
+			if !self.death {
+				self.effects.apply(status, Some(hit.owner));
 			}
+		}
+		self.take_damage(hit.damage)
+	}
 
-			if force.z > 0.0 && node.physics.velocity.z < 0.0 {
-				force.z += -node.physics.velocity.z * self.movement_force;
-			} else if force.z < 0.0 && node.physics.velocity.z > 0.0 {
-				force.z += -node.physics.velocity.z * self.movement_force;
-			} else if current_speed < 25.0 {
-				force.z *= self.movement_force;
+	/// Returns the health lost, which leaves out what the shield absorbed. Anything above
+	/// zero that leaves the player dead was the killing blow.
+	pub fn take_damage(&mut self, amount: u32) -> u32 {
+		if self.death {
+			return 0;
+		}
+		let mut amount = amount;
+		if let Some(shield) = &mut self.shield {
+			let absorbed = amount.min(shield.amount);
+			shield.amount -= absorbed;
+			amount -= absorbed;
+			if shield.amount == 0 {
+				self.shield = None;
 			}
+		}
+		let lost = amount.min(self.health);
+		self.health -= lost;
+		if self.health == 0 {
+			self.death = true;
+		}
+		lost
+	}
 
-			force.y = 0.0;
+	pub fn process(&mut self, state: &mut State, combat: &mut Combat, dt: f32) {
+		let burn = self.effects.process(dt);
+		if burn > 0 {
+			// Dealt through combat so whoever lit the fire gets the credit
+			let owner = self.effects.burn_source().unwrap_or(self.node_id);
+			combat.burn(self.node_id, owner, burn);
+		}
 
-			node.physics.force = force;
-			//log::info!("force: {:?}", node.physics.force);
-		} 
-		
-		else {
-			// We calculate force opposite of momevement to slow down the player
-			// let force = -node.physics.velocity.xz() * self.movement_force;
-			// node.physics.force = Vec3::new(force.x, 0.0, force.y);
-			//player.physics.force = glam::Vec3::ZERO;
+		// Stunned and frozen players drop whatever they were about to do
+		let can_act = self.effects.can_act();
+		if !can_act {
+			self.jump_buffer = 0.0;
+			self.dash_requested = false;
+			self.grenade_requested = false;
+		}
 
-			let force = -node.physics.velocity;
-			node.physics.force = Vec3::new(force.x, 0.0, force.z);
+		let mut ctx = ItemContext {
+			state: &mut *state,
+			combat: &mut *combat,
+			owner: self.node_id,
+			team: self.team,
+			spread_multiplier: self.stance.spread_multiplier(),
+			mana: &mut self.mana,
+			shield: &mut self.shield,
+			effects: &mut self.effects,
+			summons: &mut self.summons,
+		};
+		if can_act {
+			self.inventory.process(&mut ctx, dt);
 		}
 
-		if self.spriting {
-			let dir = node.rotation * Vec3::new(0.0, 0.0, 1.0);
-			node.physics.velocity = dir * 100.0;
+		self.mana = (self.mana + self.mana_regen * dt).min(self.max_mana);
+		if let Some(shield) = &mut self.shield {
+			shield.remaining -= dt;
+			if shield.remaining <= 0.0 {
+				self.shield = None;
+			}
 		}
+		self.update_stance(state);
+		self.update_jump(state, dt);
+		self.update_grenade(state, combat);
+
+		let node = state.nodes.get_mut(&self.node_id).unwrap();
+		// node.rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0);
+
+		let sprinting = self.is_sprinting();
+		if sprinting {
+			self.stamina = (self.stamina - SPRINT_DRAIN * dt).max(0.0);
+			self.stamina_delay = STAMINA_RECOVERY_DELAY;
+			if self.stamina <= 0.0 {
+				self.exhausted = true;
+			}
+		} else if self.stamina_delay > 0.0 {
+			self.stamina_delay = (self.stamina_delay - dt).max(0.0);
+		} else {
+			self.stamina = (self.stamina + STAMINA_RECOVERY * dt).min(self.max_stamina);
+		}
+		if self.exhausted && self.stamina >= SPRINT_RESUME_STAMINA {
+			self.exhausted = false;
+		}
+
+		let mut speed_multiplier = self.stance.speed_multiplier() * self.effects.speed_multiplier();
+		if sprinting {
+			speed_multiplier *= SPRINT_MULTIPLIER;
+		}
+		let wish_dir = match self.steering {
+			_ if !can_act => Vec3::ZERO,
+			Some(dir) => Vec3::new(dir.x, 0.0, dir.z).normalize_or_zero(),
+			None if self.movdir.is_moving() => {
+				let dir = node.rotation * self.movdir.to_vec3();
+				Vec3::new(dir.x, 0.0, dir.z).normalize_or_zero()
+			},
+			None => Vec3::ZERO,
+		};
+		node.physics.velocity = self.movement.step(node.physics.velocity, wish_dir, speed_multiplier, self.grounded, dt);
+		node.physics.force = Vec3::ZERO;
+
+		self.dash_cooldown = (self.dash_cooldown - dt).max(0.0);
+		if self.dash_requested {
+			self.dash_requested = false;
+			if self.dash_cooldown <= 0.0 && self.stamina >= DASH_COST && self.stance != Stance::Prone {
+				// Dash the way the player is moving, or forward when standing still
+				let dir = if self.movdir.is_moving() { self.movdir.to_vec3() } else { Vec3::Z };
+				let dir = node.rotation * dir;
+				let dir = Vec3::new(dir.x, 0.0, dir.z).normalize_or_zero();
+				node.physics.velocity.x = dir.x * DASH_SPEED;
+				node.physics.velocity.z = dir.z * DASH_SPEED;
+				self.stamina -= DASH_COST;
+				self.stamina_delay = STAMINA_RECOVERY_DELAY;
+				self.dash_cooldown = DASH_COOLDOWN;
+			}
+		}
+	}
+
+	fn is_sprinting(&self) -> bool {
+		self.spriting && self.stance == Stance::Standing && self.movdir.is_moving() && !self.exhausted
+	}
+
+	pub fn dash(&mut self) {
+		self.dash_requested = true;
 	}
 
 	pub fn equip(&mut self, index: usize, state: &mut State) {
@@ -231,6 +509,24 @@ impl Player {
 		}
 	}
 
+	pub fn reload(&mut self) {
+		if let Some(item) = self.inventory.get_current_item() {
+			item.reload();
+		}
+	}
+
+	/// Removes the body, its model and camera and everything in the inventory.
+	pub fn destroy(&mut self, state: &mut State) {
+		self.inventory.destroy(state);
+		if let Some(camera_id) = self.camera_id.take() {
+			state.cameras.remove(&camera_id);
+		}
+		if let Some((node_id, model_id)) = self.model.take() {
+			unload_model(state, node_id, model_id);
+		}
+		despawn_node(state, self.node_id);
+	}
+
 	pub fn drop(&mut self, state: &mut State) {
 		self.inventory.drop(state);
 	}
@@ -243,6 +539,30 @@ impl Player {
 		// self.inventory.stop_grap(state);
 	}
 	
+	pub fn throw_grenade(&mut self) {
+		self.grenade_requested = true;
+	}
+
+	fn update_grenade(&mut self, state: &mut State, combat: &mut Combat) {
+		if !self.grenade_requested {
+			return;
+		}
+		self.grenade_requested = false;
+		if self.grenades == 0 {
+			return;
+		}
+
+		let (translation, rotation) = match state.nodes.get(&self.node_id) {
+			Some(node) => (node.translation, node.rotation),
+			None => return,
+		};
+		let dir = rotation * Vec3::new(0.0, 0.0, 1.0);
+		// Thrown in a slight arc
+		let velocity = dir * GRENADE_THROW_SPEED + Vec3::new(0.0, 5.0, 0.0);
+		combat.fire_explosive(state, self.node_id, self.team, translation + dir * 1.5, velocity, GRENADE_FUSE, GRENADE);
+		self.grenades -= 1;
+	}
+
 	pub fn start_sprinting(&mut self, state: &mut State) {
 		self.spriting = true;
 	}
@@ -251,9 +571,48 @@ impl Player {
 		self.spriting = false;
 	}
 
+	/// Asks for a jump. It happens as soon as the player is on the ground, or was until
+	/// just now.
 	pub fn jump(&mut self, state: &mut State) {
-		let node = state.nodes.get_mut(&self.node_id).unwrap();
-		node.physics.velocity.y = 10.0;
+		self.jump_buffer = JUMP_BUFFER;
+	}
+
+	pub fn is_grounded(&self) -> bool {
+		self.grounded
+	}
+
+	fn update_jump(&mut self, state: &mut State, dt: f32) {
+		let node = match state.nodes.get(&self.node_id) {
+			Some(node) => node,
+			None => return,
+		};
+		let half_height = match &node.collision_shape {
+			Some(CollisionShape::Box { size }) => size.y / 2.0,
+			_ => 0.0,
+		};
+		let ground = match node.parent {
+			NodeParent::Scene(scene_id) => cast_ray_hit(state, scene_id, node.translation, Vec3::NEG_Y, half_height + GROUND_TOLERANCE),
+			_ => None,
+		};
+		self.grounded = ground.map_or(false, |hit| self.movement.walkable(hit.normal));
+		let rising = node.physics.velocity.y > 0.0;
+
+		if self.grounded {
+			self.since_grounded = 0.0;
+			if !rising {
+				self.jumping = false;
+			}
+		} else {
+			self.since_grounded += dt;
+		}
+
+		if self.jump_buffer > 0.0 && !self.jumping && self.since_grounded <= COYOTE_TIME && self.stance != Stance::Prone {
+			let node = state.nodes.get_mut(&self.node_id).unwrap();
+			node.physics.velocity.y = JUMP_VELOCITY;
+			self.jumping = true;
+			self.jump_buffer = 0.0;
+		}
+		self.jump_buffer = (self.jump_buffer - dt).max(0.0);
 	}
 
 	// pub fn start_jumping(&mut self) {
@@ -280,6 +639,56 @@ impl Player {
 		self.prone = false;
 	}
 
+	pub fn toggle_prone(&mut self, state: &mut State) {
+		self.prone = !self.prone;
+	}
+
+	pub fn stance(&self) -> Stance {
+		self.stance
+	}
+
+	/// Resizes the collision box to the stance asked for. Getting taller waits until there
+	/// is nothing above the player's head. The feet stay where they are, so the camera
+	/// attached to the node's center moves down with the stance.
+	fn update_stance(&mut self, state: &mut State) {
+		let wanted = if self.prone {
+			Stance::Prone
+		} else if self.crouching {
+			Stance::Crouching
+		} else {
+			Stance::Standing
+		};
+		if wanted == self.stance {
+			return;
+		}
+
+		let node = match state.nodes.get(&self.node_id) {
+			Some(node) => node,
+			None => return,
+		};
+		let size = match &node.collision_shape {
+			Some(CollisionShape::Box { size }) => *size,
+			_ => return,
+		};
+
+		let growth = wanted.height() - size.y;
+		if growth > 0.0 {
+			if let NodeParent::Scene(scene_id) = node.parent {
+				// The whole taller box has to fit, not just the space above the head
+				let center = node.translation + Vec3::Y * growth / 2.0;
+				let half_size = Vec3::new(size.x, wanted.height(), size.z) / 2.0 - Vec3::splat(OVERLAP_SKIN);
+				if overlaps_static(state, scene_id, center, half_size) {
+					return;
+				}
+			}
+		}
+
+		let node = state.nodes.get_mut(&self.node_id).unwrap();
+		node.collision_shape = Some(CollisionShape::Box { size: Vec3::new(size.x, wanted.height(), size.z) });
+		node.translation.y += growth / 2.0;
+		self.stance = wanted;
+	}
+
 	pub fn rotate(&mut self, dx: f32, dy: f32) {
 		// self.yaw += dx * 0.001;
 		// self.pitch += dy * 0.001;
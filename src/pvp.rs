@@ -1,13 +1,83 @@
 use pge::*;
 
-pub struct PVP {
+use crate::combat::Combat;
+use crate::combat::Target;
+use crate::controller::PlayerController;
+use crate::generated_pvp_map::GeneratedPVPMap;
+use crate::movement::MovementParams;
+use crate::player::Player;
+use crate::player::PlayerBuilder;
+use crate::settings::Settings;
+use crate::types::PVPMap;
 
+pub struct PVP {
+	map: Box<dyn PVPMap>,
+	player: Player,
+	controller: PlayerController,
+	combat: Combat,
 }
 
 impl PVP {
-	pub fn new(state: &mut pge::State, window_id: ArenaId<Window>) -> Self {
+	pub fn new(state: &mut pge::State, window_id: ArenaId<Window>, seed: Option<u64>, settings: &Settings) -> Self {
+		let main_scene_id = state.scenes.insert(Scene::new());
+		let mut map = match seed {
+			Some(seed) => GeneratedPVPMap::with_seed(state, main_scene_id, 100.0, 100.0, seed),
+			None => GeneratedPVPMap::new(state, main_scene_id, 100.0, 100.0),
+		};
+
+		let movement = MovementParams::load("assets/movement.toml").unwrap_or_else(|err| {
+			log::error!("failed to load movement params: {}", err);
+			MovementParams::default()
+		});
+		let player = PlayerBuilder::new(main_scene_id)
+			.translation(map.get_player_spawn_point())
+			.movement(movement)
+			.class(settings.class)
+			.camera(&settings.camera)
+			.build(state);
+		let player_node_id = player.node_id;
+
+		let controller = PlayerController::new(state, main_scene_id, player_node_id, player.camera_id.unwrap(), settings);
+		let ui_id = state.guis.insert(camera_view(controller.camera_id));
+		if let Some(window) = state.windows.get_mut(&window_id) {
+			window.ui = Some(ui_id);
+		}
+
+		let combat = Combat::new(state, main_scene_id);
+
 		Self {
+			map: Box::new(map),
+			player,
+			controller,
+			combat,
+		}
+	}
+
+	pub fn on_keyboard_input(&mut self, key: KeyboardKey, action: KeyAction, state: &mut State) {
+		self.controller.on_keyboard_input(&mut self.player, key, action, state);
+	}
 
+	pub fn on_mouse_input(&mut self, event: MouseEvent, state: &mut State) {
+		self.controller.on_mouse_input(&mut self.player, event, state);
+	}
+
+	pub fn on_process(&mut self, state: &mut State, dt: f32) {
+		self.controller.process(state, dt);
+		self.player.process(state, &mut self.combat, dt);
+		// There are no mobs in PVP to summon
+		self.player.summons.clear();
+
+		// No opponents yet, so only the player's own burns land
+		let targets = [Target {
+			node_id: self.player.node_id,
+			team: self.player.team,
+			radius: 1.0,
+		}];
+		for hit in self.combat.process(state, &targets, dt) {
+			if hit.target == self.player.node_id {
+				self.player.take_hit(&hit);
+			}
 		}
+		self.map.process(state);
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::types::MobArchetype;
+
+const MAX_ENTRIES_PER_TABLE: usize = 10;
+
+pub fn kill_points(archetype: MobArchetype) -> u32 {
+	match archetype {
+		MobArchetype::Melee => 100,
+		MobArchetype::Ranged => 150,
+	}
+}
+
+pub const BOSS_KILL_POINTS: u32 = 2000;
+
+pub fn wave_bonus(wave: u32) -> u32 {
+	wave * 250
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Score {
+	pub kill_points: u32,
+	pub wave_points: u32,
+	pub damage_taken: u32,
+}
+
+impl Score {
+	/// Accuracy adds up to 50% on top of the earned points and every point of damage
+	/// taken costs two.
+	pub fn total(&self, accuracy: f32) -> u32 {
+		let earned = (self.kill_points + self.wave_points) as f32;
+		let total = earned * (1.0 + accuracy.clamp(0.0, 1.0) * 0.5) - (self.damage_taken * 2) as f32;
+		total.max(0.0) as u32
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+	pub map: String,
+	/// None for runs on a freshly generated map, those share one table per map.
+	pub seed: Option<u64>,
+	pub score: u32,
+	pub wave: u32,
+	pub kills: u32,
+	pub time_survived: f32,
+	pub accuracy: f32,
+	/// Seconds since the unix epoch.
+	pub timestamp: u64,
+}
+
+impl HighScoreEntry {
+	pub fn now(map: &str, seed: Option<u64>, score: u32, wave: u32, kills: u32, time_survived: f32, accuracy: f32) -> Self {
+		let timestamp = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_secs())
+			.unwrap_or(0);
+
+		Self {
+			map: map.to_string(),
+			seed,
+			score,
+			wave,
+			kills,
+			time_survived,
+			accuracy,
+			timestamp,
+		}
+	}
+}
+
+/// Local high score table, kept per map and seed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighScores {
+	#[serde(default)]
+	pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+	pub fn path() -> PathBuf {
+		dirs::data_dir()
+			.unwrap_or_else(|| PathBuf::from("."))
+			.join("wizardwars")
+			.join("highscores.toml")
+	}
+
+	/// A missing file is an empty table.
+	pub fn load() -> anyhow::Result<Self> {
+		let path = Self::path();
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+		let content = std::fs::read_to_string(path)?;
+		let mut high_scores: HighScores = toml::from_str(&content)?;
+		high_scores.entries.sort_by(|a, b| b.score.cmp(&a.score));
+		Ok(high_scores)
+	}
+
+	pub fn save(&self) -> anyhow::Result<()> {
+		let path = Self::path();
+		if let Some(dir) = path.parent() {
+			std::fs::create_dir_all(dir)?;
+		}
+		std::fs::write(path, toml::to_string(self)?)?;
+		Ok(())
+	}
+
+	/// Adds an entry and drops whatever falls off the bottom of its table.
+	/// Returns the entry's rank starting from 1, or None if it didn't make it.
+	pub fn add(&mut self, entry: HighScoreEntry) -> Option<usize> {
+		let map = entry.map.clone();
+		let seed = entry.seed;
+		// Entries are sorted by score, a tie goes below the entries already there
+		let index = self.entries.partition_point(|e| e.score >= entry.score);
+		let rank = self.entries[..index].iter().filter(|e| e.map == map && e.seed == seed).count() + 1;
+		self.entries.insert(index, entry);
+
+		let mut kept = 0;
+		self.entries.retain(|e| {
+			if e.map != map || e.seed != seed {
+				return true;
+			}
+			kept += 1;
+			kept <= MAX_ENTRIES_PER_TABLE
+		});
+
+		if rank <= MAX_ENTRIES_PER_TABLE {
+			Some(rank)
+		} else {
+			None
+		}
+	}
+
+	pub fn table(&self, map: &str, seed: Option<u64>) -> Vec<&HighScoreEntry> {
+		self.entries.iter().filter(|e| e.map == map && e.seed == seed).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(score: u32) -> HighScoreEntry {
+		HighScoreEntry {
+			map: "generated_pvp".to_string(),
+			seed: None,
+			score,
+			wave: 1,
+			kills: 0,
+			time_survived: 0.0,
+			accuracy: 0.0,
+			timestamp: 0,
+		}
+	}
+
+	#[test]
+	fn rank_comes_from_score_not_timestamp() {
+		let mut high_scores = HighScores::default();
+		assert_eq!(high_scores.add(entry(100)), Some(1));
+		assert_eq!(high_scores.add(entry(300)), Some(1));
+		assert_eq!(high_scores.add(entry(200)), Some(2));
+		assert_eq!(high_scores.add(entry(200)), Some(3));
+	}
+
+	#[test]
+	fn entries_below_the_table_are_dropped() {
+		let mut high_scores = HighScores::default();
+		for _ in 0..MAX_ENTRIES_PER_TABLE {
+			high_scores.add(entry(100));
+		}
+		assert_eq!(high_scores.add(entry(50)), None);
+		assert_eq!(high_scores.add(entry(150)), Some(1));
+		assert_eq!(high_scores.table("generated_pvp", None).len(), MAX_ENTRIES_PER_TABLE);
+	}
+
+	#[test]
+	fn seeded_runs_have_their_own_table() {
+		let mut high_scores = HighScores::default();
+		high_scores.add(entry(100));
+		let mut seeded = entry(50);
+		seeded.seed = Some(7);
+		assert_eq!(high_scores.add(seeded), Some(1));
+		assert_eq!(high_scores.table("generated_pvp", None).len(), 1);
+	}
+}
@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use log::LevelFilter;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::args::Args;
+use crate::classes::WizardClass;
+use crate::input::Bindings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+	pub width: u32,
+	pub height: u32,
+	pub fullscreen: bool,
+	pub lock_cursor: bool,
+}
+
+impl Default for WindowSettings {
+	fn default() -> Self {
+		Self {
+			width: 1280,
+			height: 720,
+			fullscreen: false,
+			lock_cursor: true,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+	/// Vertical field of view in degrees.
+	pub fov: f32,
+	pub view_distance: f32,
+	/// How far behind the player the third person camera sits.
+	pub third_person_distance: f32,
+}
+
+impl Default for CameraSettings {
+	fn default() -> Self {
+		Self {
+			fov: 70.0,
+			view_distance: 1000.0,
+			third_person_distance: 4.0,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MouseSettings {
+	pub sensitivity: f32,
+	pub invert_y: bool,
+	pub smoothing: f32,
+}
+
+impl Default for MouseSettings {
+	fn default() -> Self {
+		Self {
+			sensitivity: 0.002,
+			invert_y: false,
+			smoothing: 0.0,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+	/// 0.0 is muted, 1.0 is full volume.
+	pub volume: f32,
+}
+
+impl Default for AudioSettings {
+	fn default() -> Self {
+		Self {
+			volume: 0.8,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+	/// One of error, warn, info, debug or trace.
+	pub log_level: String,
+	pub class: WizardClass,
+	pub window: WindowSettings,
+	pub camera: CameraSettings,
+	pub mouse: MouseSettings,
+	pub audio: AudioSettings,
+	pub bindings: Bindings,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			log_level: "info".to_string(),
+			class: WizardClass::default(),
+			window: WindowSettings::default(),
+			camera: CameraSettings::default(),
+			mouse: MouseSettings::default(),
+			audio: AudioSettings::default(),
+			bindings: Bindings::default(),
+		}
+	}
+}
+
+impl Settings {
+	pub fn path() -> PathBuf {
+		dirs::config_dir()
+			.unwrap_or_else(|| PathBuf::from("."))
+			.join("wizardwars")
+			.join("settings.toml")
+	}
+
+	/// A missing file gives the default settings.
+	pub fn load() -> anyhow::Result<Self> {
+		let path = Self::path();
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+		let content = std::fs::read_to_string(path)?;
+		let mut settings: Settings = toml::from_str(&content)?;
+		settings.bindings = settings.bindings.with_defaults();
+		settings.audio.volume = settings.audio.volume.clamp(0.0, 1.0);
+		Ok(settings)
+	}
+
+	pub fn save(&self) -> anyhow::Result<()> {
+		let path = Self::path();
+		if let Some(dir) = path.parent() {
+			std::fs::create_dir_all(dir)?;
+		}
+		std::fs::write(path, toml::to_string(self)?)?;
+		Ok(())
+	}
+
+	/// Changes the settings stored on disk. Command line overrides of the running game
+	/// stay out of the file, and a file that fails to load is left alone.
+	pub fn update(change: impl FnOnce(&mut Settings)) -> anyhow::Result<()> {
+		let mut settings = Self::load()?;
+		change(&mut settings);
+		settings.save()
+	}
+
+	/// Command line arguments and `WIZARDWARS_LOG_LEVEL` take precedence over the file.
+	pub fn apply_overrides(&mut self, args: &Args) {
+		if let Ok(level) = std::env::var("WIZARDWARS_LOG_LEVEL") {
+			self.log_level = level;
+		}
+		if let Some(level) = &args.log_level {
+			self.log_level = level.clone();
+		}
+		if let Some(width) = args.width {
+			self.window.width = width;
+		}
+		if let Some(height) = args.height {
+			self.window.height = height;
+		}
+		if args.fullscreen {
+			self.window.fullscreen = true;
+		}
+		if args.windowed {
+			self.window.fullscreen = false;
+		}
+		if let Some(fov) = args.fov {
+			self.camera.fov = fov;
+		}
+		if let Some(sensitivity) = args.sensitivity {
+			self.mouse.sensitivity = sensitivity;
+		}
+		if let Some(class) = args.class {
+			self.class = class;
+		}
+	}
+
+	pub fn log_level(&self) -> LevelFilter {
+		match self.log_level.as_str() {
+			"error" => LevelFilter::Error,
+			"warn" => LevelFilter::Warn,
+			"info" => LevelFilter::Info,
+			"debug" => LevelFilter::Debug,
+			"trace" => LevelFilter::Trace,
+			_ => LevelFilter::Info
+		}
+	}
+}
@@ -0,0 +1,273 @@
+use pge::*;
+
+use crate::combat::AreaEffect;
+use crate::combat::Falloff;
+use crate::combat::Shield;
+use crate::effects::StatusEffect;
+use crate::types::MobArchetype;
+use crate::types::SummonRequest;
+use crate::types::Item;
+use crate::types::ItemContext;
+use crate::utility::find_safe_destination;
+
+/// What happens when a spell finishes casting.
+#[derive(Debug, Clone, Copy)]
+pub enum SpellEffect {
+	Projectile {
+		speed: f32,
+		damage: u32,
+		/// Replaces the direct hit damage when set.
+		explosion: Option<AreaEffect>,
+		/// Applied to the target of a direct hit.
+		status: Option<StatusEffect>,
+	},
+	/// A status effect on the caster.
+	Buff(StatusEffect),
+	/// Temporary mobs fighting on the caster's team.
+	Summon {
+		archetype: MobArchetype,
+		count: u32,
+		lifetime: f32,
+	},
+	Shield {
+		amount: u32,
+		duration: f32,
+	},
+	/// Teleports the caster along their view direction.
+	Blink {
+		distance: f32,
+	},
+}
+
+#[derive(Debug, Clone)]
+pub struct SpellDefinition {
+	pub name: &'static str,
+	pub mana_cost: f32,
+	/// Seconds between starting the cast and the effect.
+	pub cast_time: f32,
+	/// Seconds after a cast before the spell can be cast again.
+	pub cooldown: f32,
+	pub effect: SpellEffect,
+}
+
+impl SpellDefinition {
+	pub fn fireball() -> Self {
+		Self {
+			name: "Fireball",
+			mana_cost: 20.0,
+			cast_time: 0.4,
+			cooldown: 0.8,
+			effect: SpellEffect::Projectile {
+				speed: 30.0,
+				damage: 60,
+				explosion: Some(AreaEffect {
+					radius: 4.0,
+					damage: 60,
+					falloff: Falloff::Linear,
+					impulse: 300.0,
+					status: Some(StatusEffect::burning(5.0, 4.0)),
+				}),
+				status: None,
+			},
+		}
+	}
+
+	pub fn frost_bolt() -> Self {
+		Self {
+			name: "Frost Bolt",
+			mana_cost: 10.0,
+			cast_time: 0.0,
+			cooldown: 0.3,
+			effect: SpellEffect::Projectile {
+				speed: 60.0,
+				damage: 20,
+				explosion: None,
+				status: Some(StatusEffect::slowed(0.4, 3.0)),
+			},
+		}
+	}
+
+	pub fn arcane_shield() -> Self {
+		Self {
+			name: "Arcane Shield",
+			mana_cost: 35.0,
+			cast_time: 0.6,
+			cooldown: 10.0,
+			effect: SpellEffect::Shield { amount: 50, duration: 8.0 },
+		}
+	}
+
+	pub fn haste() -> Self {
+		Self {
+			name: "Haste",
+			mana_cost: 30.0,
+			cast_time: 0.2,
+			cooldown: 15.0,
+			effect: SpellEffect::Buff(StatusEffect::haste(0.5, 6.0)),
+		}
+	}
+
+	pub fn summon_minion() -> Self {
+		Self {
+			name: "Summon Minion",
+			mana_cost: 50.0,
+			cast_time: 1.0,
+			cooldown: 20.0,
+			effect: SpellEffect::Summon {
+				archetype: MobArchetype::Melee,
+				count: 1,
+				lifetime: 30.0,
+			},
+		}
+	}
+
+	pub fn blink() -> Self {
+		Self {
+			name: "Blink",
+			mana_cost: 25.0,
+			cast_time: 0.0,
+			cooldown: 4.0,
+			effect: SpellEffect::Blink { distance: 12.0 },
+		}
+	}
+}
+
+/// A spell slotted into the inventory like any other item. Primary action casts it and
+/// holding the button keeps casting whenever the cooldown and mana allow.
+pub struct Spell {
+	definition: SpellDefinition,
+	held: bool,
+	/// A press that hasn't been turned into a cast yet.
+	requested: bool,
+	/// Seconds spent on the current cast.
+	casting: Option<f32>,
+	cooldown: f32,
+}
+
+impl Spell {
+	pub fn new(definition: SpellDefinition) -> Self {
+		Self {
+			definition,
+			held: false,
+			requested: false,
+			casting: None,
+			cooldown: 0.0,
+		}
+	}
+
+	/// Nothing is spent if the spell fizzles.
+	fn cast(&mut self, ctx: &mut ItemContext) {
+		match self.definition.effect {
+			SpellEffect::Projectile { speed, damage, explosion, status } => {
+				let (translation, rotation) = match ctx.state.nodes.get(&ctx.owner) {
+					Some(node) => (node.translation, node.rotation),
+					None => return,
+				};
+				let dir = rotation * Vec3::new(0.0, 0.0, 1.0);
+				let translation = translation + dir * 2.0;
+				match (explosion, status) {
+					(Some(effect), _) => ctx.combat.fire_explosive(ctx.state, ctx.owner, ctx.team, translation, dir * speed, 3.0, effect),
+					(None, Some(status)) => ctx.combat.fire_with_status(ctx.state, ctx.owner, ctx.team, translation, dir, speed, damage, status),
+					(None, None) => ctx.combat.fire(ctx.state, ctx.owner, ctx.team, translation, dir, speed, damage),
+				}
+			},
+			SpellEffect::Shield { amount, duration } => {
+				*ctx.shield = Some(Shield { amount, remaining: duration });
+			},
+			SpellEffect::Buff(status) => {
+				ctx.effects.apply(status, Some(ctx.owner));
+			},
+			SpellEffect::Summon { archetype, count, lifetime } => {
+				let (translation, rotation) = match ctx.state.nodes.get(&ctx.owner) {
+					Some(node) => (node.translation, node.rotation),
+					None => return,
+				};
+				let forward = rotation * Vec3::new(0.0, 0.0, 1.0);
+				let forward = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+				let right = Vec3::new(forward.z, 0.0, -forward.x);
+				for i in 0..count {
+					// Side by side in front of the caster
+					let offset = (i as f32 - (count - 1) as f32 / 2.0) * 2.0;
+					ctx.summons.push(SummonRequest {
+						summoner: ctx.owner,
+						team: ctx.team,
+						translation: translation + forward * 3.0 + right * offset + Vec3::new(0.0, 1.0, 0.0),
+						archetype,
+						lifetime,
+					});
+				}
+			},
+			SpellEffect::Blink { distance } => {
+				let node = match ctx.state.nodes.get(&ctx.owner) {
+					Some(node) => node,
+					None => return,
+				};
+				let half_size = match node.collision_shape {
+					Some(CollisionShape::Box { size }) => size / 2.0,
+					_ => Vec3::splat(0.5),
+				};
+				let dir = node.rotation * Vec3::new(0.0, 0.0, 1.0);
+				let destination = match find_safe_destination(ctx.state, ctx.combat.scene_id(), node.translation, dir, distance, half_size) {
+					Some(destination) => destination,
+					None => {
+						log::info!("no room to blink");
+						return;
+					},
+				};
+				let node = ctx.state.nodes.get_mut(&ctx.owner).unwrap();
+				node.translation = destination;
+				node.physics.velocity = Vec3::ZERO;
+			},
+		}
+
+		log::info!("cast {}", self.definition.name);
+		*ctx.mana -= self.definition.mana_cost;
+		self.cooldown = self.definition.cooldown;
+	}
+}
+
+impl Item for Spell {
+	fn name(&self) -> &str {
+		self.definition.name
+	}
+
+	fn start_primary_action(&mut self, state: &mut State) {
+		self.held = true;
+		self.requested = true;
+	}
+
+	fn stop_primary_action(&mut self, state: &mut State) {
+		self.held = false;
+	}
+
+	fn hide(&mut self, state: &mut State) {
+		// Switching away interrupts the cast
+		self.casting = None;
+		self.held = false;
+		self.requested = false;
+	}
+
+	fn process(&mut self, ctx: &mut ItemContext, dt: f32) {
+		self.cooldown = (self.cooldown - dt).max(0.0);
+
+		if self.casting.is_none() && (self.requested || self.held) {
+			self.requested = false;
+			if self.cooldown <= 0.0 && *ctx.mana >= self.definition.mana_cost {
+				self.casting = Some(0.0);
+			}
+		}
+
+		if let Some(elapsed) = self.casting {
+			let elapsed = elapsed + dt;
+			if elapsed < self.definition.cast_time {
+				self.casting = Some(elapsed);
+				return;
+			}
+			self.casting = None;
+			// Mana may have been spent elsewhere while casting
+			if *ctx.mana >= self.definition.mana_cost {
+				self.cast(ctx);
+			}
+		}
+	}
+}
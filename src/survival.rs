@@ -1,178 +1,494 @@
 use std::time::Instant;
 use pge::*;
-use crate::ak47::AK47;
+use rand::Rng;
+use crate::boss::Boss;
+use crate::boss::BossAction;
+use crate::combat::Combat;
+use crate::combat::Target;
+use crate::controller::CameraMode;
+use crate::controller::PlayerController;
 use crate::dark_dungeon::DarkDungeon;
 use crate::generated_pvp_map::GeneratedPVPMap;
-use crate::inventory::Inventory;
-use crate::katana::Katana;
+use crate::hud::HitMarker;
+use crate::hud::HotbarSlot;
+use crate::hud::Hud;
+use crate::hud::HudState;
+use crate::input::Action;
 // use crate::mobs::spawn_mob;
 use crate::mobs::MobSpawner;
+use crate::movement::MovementParams;
 use crate::npc::Npc;
 use crate::player::Player;
+use crate::player::PlayerBuilder;
+use crate::score::kill_points;
+use crate::score::wave_bonus;
+use crate::score::HighScoreEntry;
+use crate::score::HighScores;
+use crate::score::Score;
+use crate::score::BOSS_KILL_POINTS;
+use crate::settings::Settings;
+use crate::types::Crosshair;
+use crate::types::MobArchetype;
 use crate::types::SurvivalMap;
+use crate::utility::despawn_scene;
+use crate::waves::WaveConfig;
+use crate::waves::WaveDefinition;
+
+/// Seconds of countdown before each wave.
+const PRE_WAVE_COUNTDOWN: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurvivalPhase {
+	/// Counting down to the next wave.
+	PreWave { remaining: f32 },
+	/// Mobs of the current wave are spawning or still alive.
+	Active,
+	/// Resting after a cleared wave. Can be skipped by the player.
+	Intermission { remaining: f32 },
+	GameOver,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SurvivalStats {
+	pub kills: u32,
+	pub shots_fired: u32,
+	pub shots_hit: u32,
+	pub time_survived: f32,
+}
+
+impl SurvivalStats {
+	pub fn accuracy(&self) -> f32 {
+		if self.shots_fired == 0 {
+			return 0.0;
+		}
+		self.shots_hit as f32 / self.shots_fired as f32
+	}
+}
 
 pub struct Survival {
+	window_id: ArenaId<Window>,
+	requested_seed: Option<u64>,
 	player: Player,
 	main_scene_id: ArenaId<Scene>,
 	wave: u32,
 	enemies: Vec<Npc>,
-	max_enemies: u32,
-	enemies_spawned: u32,
+	/// Mobs summoned by the player, fighting on its side.
+	minions: Vec<Npc>,
+	wave_config: WaveConfig,
+	wave_definition: WaveDefinition,
+	spawn_queue: Vec<MobArchetype>,
 	since_last_spawn: Instant,
+	phase: SurvivalPhase,
+	stats: SurvivalStats,
+	score: Score,
+	high_scores: Vec<HighScoreEntry>,
 	map: Box<dyn SurvivalMap>,
-	spawner: MobSpawner
+	spawner: MobSpawner,
+	combat: Combat,
+	boss: Option<Boss>,
+	controller: PlayerController,
+	hud: Hud,
+	/// Marker to show and how many seconds it has left.
+	hit_marker: Option<(HitMarker, f32)>,
 }
 
 impl Survival {
-	pub fn new(state: &mut State, window_id: ArenaId<Window>) -> Self {
+	/// Without a seed every game gets a freshly generated map.
+	pub fn new(state: &mut State, window_id: ArenaId<Window>, seed: Option<u64>, settings: Settings) -> Self {
 		let main_scene = Scene::new();
 		let main_scene_id = state.scenes.insert(main_scene);
-		let map = GeneratedPVPMap::new(state, main_scene_id, 100.0, 100.0);
+		let map = match seed {
+			Some(seed) => GeneratedPVPMap::with_seed(state, main_scene_id, 100.0, 100.0, seed),
+			None => GeneratedPVPMap::new(state, main_scene_id, 100.0, 100.0),
+		};
+		// Pass it with --seed to replay the map
+		log::info!("map {} with seed {}", map.name(), map.seed());
 		// let map = DarkDungeon::create(state, main_scene_id);
 
-		let mut player_node = Node::new();
-		player_node.parent = NodeParent::Scene(main_scene_id);
-		player_node.physics.mass = 100.0;
-		player_node.physics.typ = PhycisObjectType::Dynamic;
-		player_node.collision_shape = Some(CollisionShape::Box { size: Vec3::new(0.5, 1.8, 0.5) });
-		player_node.translation = Vec3::new(0.0, 1.0, 0.0);
-		let player_node_id = state.nodes.insert(player_node);
-		let inventory = Inventory::new(4);
-		let mut player = Player::new(player_node_id, inventory);
-		player.inventory.add_item(AK47::new(state, main_scene_id));
-		player.inventory.add_item(Katana::new(state, main_scene_id));
-
-		let mut camera = pge::Camera::new();
-		camera.zfar = 1000.0;
-		camera.node_id = Some(player_node_id);
-		let camera_id = state.cameras.insert(camera);
-
-		let ui = stack(&[
-			camera_view(camera_id),
-			row(&[
-				rect().background_color(Color::BLUE),
-				rect().background_color(Color::RED),
-				rect().background_color(Color::CYAN),
-				rect().background_color(Color::WHITE)
-			]).height(0.1).anchor_bottom()
-		]);
-		let ui_id = state.guis.insert(ui);	
-		let window = state.windows.get_mut(&window_id).unwrap();
-		window.ui = Some(ui_id);
-
-		let spawner = MobSpawner::new(state, main_scene_id);
+		let movement = MovementParams::load("assets/movement.toml").unwrap_or_else(|err| {
+			log::error!("failed to load movement params: {}", err);
+			MovementParams::default()
+		});
+		let player = PlayerBuilder::new(main_scene_id)
+			.translation(Vec3::new(0.0, 1.0, 0.0))
+			.movement(movement.clone())
+			.class(settings.class)
+			.camera(&settings.camera)
+			.build(state);
+		let player_node_id = player.node_id;
+		log::info!("playing as {}", settings.class.name());
+
+		let controller = PlayerController::new(state, main_scene_id, player_node_id, player.camera_id.unwrap(), &settings);
+		let hud = Hud::new(state, window_id, controller.camera_id);
+
+		let spawner = MobSpawner::new(state, main_scene_id, movement);
+		let combat = Combat::new(state, main_scene_id);
+		let wave_config = WaveConfig::load("assets/waves.toml").unwrap_or_else(|err| {
+			log::error!("failed to load wave config: {}", err);
+			WaveConfig::default()
+		});
+		let wave_definition = wave_config.wave(1);
 
 		Self {
+			window_id,
+			requested_seed: seed,
 			wave: 0,
 			player,
 			main_scene_id,
 			enemies: Vec::new(),
-			max_enemies: 10,
-			enemies_spawned: 0,
+			minions: Vec::new(),
+			wave_config,
+			wave_definition,
+			spawn_queue: Vec::new(),
 			since_last_spawn: Instant::now(),
+			phase: SurvivalPhase::PreWave { remaining: PRE_WAVE_COUNTDOWN },
+			stats: SurvivalStats::default(),
+			score: Score::default(),
+			high_scores: Vec::new(),
 			map: Box::new(map),
-			spawner
+			spawner,
+			combat,
+			boss: None,
+			controller,
+			hud,
+			hit_marker: None,
 		}
 	}
 
-	fn start_next_wave(&mut self, state: &mut State) {
-		// How to despawn enemies ??
-		self.wave += 1;
-		self.max_enemies += 5;
-		self.enemies_spawned = 0;
+	fn banner(&self) -> Option<String> {
+		match self.phase {
+			SurvivalPhase::PreWave { remaining } => Some(format!("Wave {} in {}…", self.wave + 1, remaining.ceil() as u32)),
+			SurvivalPhase::Active => None,
+			SurvivalPhase::Intermission { remaining } => Some(format!("Wave {} cleared! Next wave in {}… (Enter to skip)", self.wave, (remaining + PRE_WAVE_COUNTDOWN).ceil() as u32)),
+			SurvivalPhase::GameOver => {
+				let seconds = self.stats.time_survived as u32;
+				let mut banner = format!(
+					"Game over! Score {} - wave {} - {} kills - survived {}:{:02} - {:.0}% accuracy (R to restart)",
+					self.score.total(self.stats.accuracy()),
+					self.wave,
+					self.stats.kills,
+					seconds / 60,
+					seconds % 60,
+					self.stats.accuracy() * 100.0
+				);
+				for (i, entry) in self.high_scores.iter().take(5).enumerate() {
+					banner.push_str(&format!("\n{}. {} (wave {})", i + 1, entry.score, entry.wave));
+				}
+				Some(banner)
+			},
+		}
 	}
 
-	pub fn on_mouse_input(&mut self, event: MouseEvent, state: &mut State) {
-		match event {
-			MouseEvent::Moved { dx, dy } => {
-				let node = state.nodes.get_mut(&self.player.node_id).unwrap();
-				let (a, b, c) = node.rotation.to_euler(EulerRot::YXZ);
-				let yaw = a + dx * 0.002;
-				let pitch = b + dy * 0.002;
-				node.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
-				self.player.on_mouse_moved(dx, dy, state);
+	fn hotbar(&self) -> Vec<HotbarSlot> {
+		let inventory = &self.player.inventory;
+		(0..inventory.capacity()).map(|index| HotbarSlot {
+			name: inventory.slot(index).map(|item| item.name().to_string()),
+			active: inventory.active() == Some(index),
+		}).collect()
+	}
+
+	fn hud_state(&self) -> HudState {
+		let boss_remaining = if self.boss.is_some() { 1 } else { 0 };
+		HudState {
+			health: self.player.health as f32 / self.player.max_health as f32,
+			stamina: self.player.stamina / self.player.max_stamina,
+			mana: self.player.mana / self.player.max_mana,
+			ammo: self.player.inventory.current_item().and_then(|item| item.ammo()),
+			wave: self.wave,
+			enemies_remaining: self.enemies.len() as u32 + self.spawn_queue.len() as u32 + boss_remaining,
+			boss_health: self.boss.as_ref().map(|b| b.health_fraction()),
+			banner: self.banner(),
+			hotbar: self.hotbar(),
+			crosshair: self.player.inventory.current_item().map_or(Crosshair::Dot, |item| item.crosshair()),
+			hit_marker: self.hit_marker.map(|(marker, _)| marker),
+			effects: self.player.effects.list().into_iter().map(|(kind, remaining)| (kind, remaining.ceil() as u32)).collect(),
+		}
+	}
+
+	fn set_phase(&mut self, phase: SurvivalPhase) {
+		if std::mem::discriminant(&phase) != std::mem::discriminant(&self.phase) {
+			log::info!("survival phase {:?}", phase);
+		}
+		self.phase = phase;
+	}
+
+	fn on_game_over(&mut self, state: &mut State) {
+		let accuracy = self.stats.accuracy();
+		let score = self.score.total(accuracy);
+		log::info!("game over on wave {} with score {}", self.wave, score);
+		self.controller.set_mode(state, CameraMode::Spectator);
+
+		let entry = HighScoreEntry::now(self.map.name(), self.requested_seed, score, self.wave, self.stats.kills, self.stats.time_survived, accuracy);
+		// A file that doesn't load is left alone rather than replaced by this one run
+		let mut high_scores = match HighScores::load() {
+			Ok(high_scores) => high_scores,
+			Err(err) => {
+				log::error!("failed to load high scores, not recording this run: {}", err);
+				return;
+			}
+		};
+		if let Some(rank) = high_scores.add(entry) {
+			log::info!("new high score, rank {}", rank);
+		}
+		if let Err(err) = high_scores.save() {
+			log::error!("failed to save high scores: {}", err);
+		}
+		self.high_scores = high_scores.table(self.map.name(), self.requested_seed).into_iter().cloned().collect();
+	}
+
+	fn update_phase(&mut self, state: &mut State, dt: f32) {
+		if self.player.death {
+			self.set_phase(SurvivalPhase::GameOver);
+			self.on_game_over(state);
+			return;
+		}
+
+		match self.phase {
+			SurvivalPhase::PreWave { remaining } => {
+				let remaining = remaining - dt;
+				if remaining <= 0.0 {
+					self.start_next_wave(state);
+				} else {
+					self.set_phase(SurvivalPhase::PreWave { remaining });
+				}
 			},
-			MouseEvent::Pressed { button } => {
-				match button {
-					MouseButton::Left => self.player.start_primary_action(state),
-					MouseButton::Right => self.player.start_secondary_action(state),
-					MouseButton::Middle => self.player.start_third_action(state),
-					_ => {}
+			SurvivalPhase::Active => {
+				if self.enemies.is_empty() && self.boss.is_none() && self.spawn_queue.is_empty() {
+					log::info!("wave {} cleared", self.wave);
+					self.score.wave_points += wave_bonus(self.wave);
+					self.set_phase(SurvivalPhase::Intermission { remaining: self.wave_definition.intermission });
 				}
 			},
-			MouseEvent::Released { button } => {
-				match button {
-					MouseButton::Left => self.player.stop_primary_action(state),
-					MouseButton::Right => self.player.stop_secondary_action(state),
-					MouseButton::Middle => self.player.stop_third_action(state),
-					_ => {}
+			SurvivalPhase::Intermission { remaining } => {
+				let remaining = remaining - dt;
+				if remaining <= 0.0 {
+					self.set_phase(SurvivalPhase::PreWave { remaining: PRE_WAVE_COUNTDOWN });
+				} else {
+					self.set_phase(SurvivalPhase::Intermission { remaining });
 				}
 			},
-			_ => {}
+			SurvivalPhase::GameOver => {},
 		}
 	}
 
-	pub fn on_keyboard_input(&mut self, key: KeyboardKey, action: KeyAction, state: &mut State) {
-		match action {
-			KeyAction::Pressed => {
-				match key {
-					KeyboardKey::W => self.player.movdir.forward = true,
-					KeyboardKey::S => self.player.movdir.backward = true,
-					KeyboardKey::A => self.player.movdir.left = true,
-					KeyboardKey::D => self.player.movdir.right = true,
-					KeyboardKey::ShiftLeft => self.player.start_sprinting(state),
-					KeyboardKey::Space => self.player.jump(state),
-					KeyboardKey::G => self.player.drop(state),
-					KeyboardKey::F => self.player.start_grap(state),
-					KeyboardKey::Digit1 => self.player.equip(0, state),
-					KeyboardKey::Digit2 => self.player.equip(1, state),
-					KeyboardKey::Digit3 => self.player.equip(2, state),
-					KeyboardKey::Digit4 => self.player.equip(3, state),
-					KeyboardKey::Digit5 => self.player.equip(4, state),
-					KeyboardKey::Digit6 => self.player.equip(5, state),
-					_ => {}
-				}
-			},
-			KeyAction::Released => {
-				match key {
-					KeyboardKey::W => self.player.movdir.forward = false,
-					KeyboardKey::S => self.player.movdir.backward = false,
-					KeyboardKey::A => self.player.movdir.left = false,
-					KeyboardKey::D => self.player.movdir.right = false,
-					KeyboardKey::ShiftLeft => self.player.stop_sprinting(state),
-					KeyboardKey::Space => self.player.jumping = false,
-					KeyboardKey::F => self.player.stop_grap(state),
-					_ => {}
-				}
-			},
+	/// Tears down everything this game spawned and starts over in the same window.
+	pub fn restart(&mut self, state: &mut State) {
+		log::info!("restarting survival");
+		let window_id = self.window_id;
+		let seed = self.requested_seed;
+		let settings = self.controller.settings().clone();
+		self.teardown(state);
+		*self = Survival::new(state, window_id, seed, settings);
+	}
+
+	fn teardown(&mut self, state: &mut State) {
+		self.player.destroy(state);
+		for npc in self.enemies.iter_mut().chain(&mut self.minions) {
+			npc.player.destroy(state);
+		}
+		if let Some(boss) = &mut self.boss {
+			boss.npc.player.destroy(state);
+		}
+		self.combat.destroy(state);
+		self.map.destroy(state);
+		despawn_scene(state, self.main_scene_id);
+		self.controller.destroy(state);
+		self.hud.destroy(state);
+		self.enemies.clear();
+		self.minions.clear();
+		self.boss = None;
+	}
+
+	pub fn skip_intermission(&mut self) {
+		if let SurvivalPhase::Intermission { .. } = self.phase {
+			self.set_phase(SurvivalPhase::PreWave { remaining: PRE_WAVE_COUNTDOWN });
+		}
+	}
+
+	fn start_next_wave(&mut self, state: &mut State) {
+		self.wave += 1;
+		self.wave_definition = self.wave_config.wave(self.wave);
+		self.spawn_queue = self.wave_definition.spawn_queue();
+		self.set_phase(SurvivalPhase::Active);
+		log::info!("wave {} with {} mobs", self.wave, self.wave_definition.total_mobs());
+
+		if self.wave_config.is_boss_wave(self.wave) {
+			log::info!("boss wave {}", self.wave);
+			let npc = self.spawner.spawn_boss(state, self.map.get_mob_spawn_point());
+			self.boss = Some(Boss::new(npc));
+		}
+	}
+
+	fn handle_boss(&mut self, state: &mut State, targets: &[Target], dt: f32) {
+		let boss = match &mut self.boss {
+			Some(boss) => boss,
+			None => return,
 		};
 
-		log::info!("Pressed keys: {:?}", self.player.movdir);
+		for action in boss.process(state, &self.player, targets, &mut self.combat, dt) {
+			match action {
+				BossAction::Summon { count } => {
+					let center = state.nodes.get(&boss.npc.player.node_id).unwrap().translation;
+					let mut rng = rand::thread_rng();
+					for _ in 0..count {
+						let offset = Vec3::new(rng.gen_range(-5.0..5.0), 2.0, rng.gen_range(-5.0..5.0));
+						self.enemies.push(self.spawner.spawn(state, center + offset, MobArchetype::Melee));
+					}
+				},
+			}
+		}
+	}
+
+	fn on_boss_killed(&mut self, state: &mut State, mut boss: Boss) {
+		log::info!("boss killed on wave {}", self.wave);
+		boss.npc.player.destroy(state);
+		// Guaranteed reward for taking the boss down
+		self.player.health = self.player.max_health;
+		self.score.kill_points += BOSS_KILL_POINTS;
+	}
+
+	pub fn on_mouse_input(&mut self, event: MouseEvent, state: &mut State) {
+		for (action, pressed) in self.controller.on_mouse_input(&mut self.player, event, state) {
+			self.on_action(action, pressed, state);
+		}
+	}
+
+	pub fn on_keyboard_input(&mut self, key: KeyboardKey, action: KeyAction, state: &mut State) {
+		for (action, pressed) in self.controller.on_keyboard_input(&mut self.player, key, action, state) {
+			self.on_action(action, pressed, state);
+		}
+	}
+
+	fn on_action(&mut self, action: Action, pressed: bool, state: &mut State) {
+		match (action, pressed) {
+			(Action::Restart, true) if self.phase == SurvivalPhase::GameOver => self.restart(state),
+			(Action::SkipIntermission, true) => self.skip_intermission(),
+			_ => {}
+		}
+	}
+
+	fn targets(&self) -> Vec<Target> {
+		let mut targets = vec![Target {
+			node_id: self.player.node_id,
+			team: self.player.team,
+			radius: 1.0,
+		}];
+		for npc in self.enemies.iter().chain(&self.minions) {
+			targets.push(Target {
+				node_id: npc.player.node_id,
+				team: npc.player.team,
+				radius: 1.5,
+			});
+		}
+		if let Some(boss) = &self.boss {
+			targets.push(Target {
+				node_id: boss.npc.player.node_id,
+				team: boss.npc.player.team,
+				radius: 2.5,
+			});
+		}
+		targets
 	}
 
 	pub fn on_process(&mut self, state: &mut State, dt: f32) {
-		self.player.process(state, dt);
-		let mut all_enemies_dead = true;
+		if self.phase == SurvivalPhase::GameOver {
+			self.controller.process(state, dt);
+			let hud_state = self.hud_state();
+			self.hud.update(state, hud_state);
+			return;
+		}
+
+		self.controller.process(state, dt);
+		self.player.process(state, &mut self.combat, dt);
+		for request in std::mem::take(&mut self.player.summons) {
+			self.minions.push(self.spawner.spawn_minion(state, &request));
+		}
+
+		let targets = self.targets();
 		for enemy in &mut self.enemies {
-			enemy.process(state, &self.player, dt);
-			if !enemy.player.death {
-				all_enemies_dead = false;
+			enemy.process(state, &targets, &mut self.combat, dt);
+		}
+		for minion in &mut self.minions {
+			minion.process(state, &targets, &mut self.combat, dt);
+		}
+		self.handle_boss(state, &targets, dt);
+
+		// Attacks of the player that landed, an explosion hitting three mobs is one hit
+		let mut landed = Vec::new();
+		for hit in self.combat.process(state, &targets, dt) {
+			let by_player = hit.owner == self.player.node_id;
+			// Kills by the player's minions count as the player's
+			let credited = by_player || self.minions.iter().any(|m| m.player.node_id == hit.owner && m.summoner == Some(self.player.node_id));
+			let mut killed = false;
+			if hit.target == self.player.node_id {
+				self.score.damage_taken += self.player.take_hit(&hit);
+			} else if let Some(enemy) = self.enemies.iter_mut().find(|e| e.player.node_id == hit.target) {
+				killed = enemy.player.take_hit(&hit) > 0 && enemy.player.death;
+				if killed && credited {
+					self.score.kill_points += kill_points(enemy.archetype);
+				}
+			} else if let Some(boss) = self.boss.as_mut().filter(|b| b.npc.player.node_id == hit.target) {
+				killed = boss.npc.player.take_hit(&hit) > 0 && boss.npc.player.death;
+			} else if let Some(minion) = self.minions.iter_mut().find(|m| m.player.node_id == hit.target) {
+				minion.player.take_hit(&hit);
 			}
+
+			if killed && credited {
+				self.stats.kills += 1;
+			}
+			if by_player {
+				// Burn ticks can kill but are not shots
+				if !hit.burn && !landed.contains(&hit.attack) {
+					landed.push(hit.attack);
+					self.stats.shots_hit += 1;
+				}
+				if killed {
+					self.hit_marker = Some((HitMarker::Kill, 0.4));
+				} else if !hit.burn && self.hit_marker.map_or(true, |(marker, _)| marker != HitMarker::Kill) {
+					self.hit_marker = Some((HitMarker::Hit, 0.2));
+				}
+			}
+		}
+		self.stats.shots_fired += self.combat.take_fired().iter().filter(|owner| **owner == self.player.node_id).count() as u32;
+		self.stats.time_survived += dt;
+
+		if let Some((marker, remaining)) = self.hit_marker {
+			let remaining = remaining - dt;
+			self.hit_marker = if remaining > 0.0 { Some((marker, remaining)) } else { None };
+		}
+
+		if self.boss.as_ref().map_or(false, |b| b.npc.player.death) {
+			let boss = self.boss.take().unwrap();
+			self.on_boss_killed(state, boss);
 		}
 
-		if all_enemies_dead && self.enemies_spawned >= self.max_enemies {
-			self.start_next_wave(state);
+		for enemy in &mut self.enemies {
+			if enemy.player.death {
+				enemy.player.destroy(state);
+			}
+		}
+		self.enemies.retain(|e| !e.player.death);
+
+		for minion in &mut self.minions {
+			if minion.player.death || minion.expired() {
+				minion.player.destroy(state);
+			}
 		}
+		self.minions.retain(|m| !m.player.death && !m.expired());
+
+		self.update_phase(state, dt);
 
-		if self.enemies_spawned < self.max_enemies {
+		if self.phase == SurvivalPhase::Active && !self.spawn_queue.is_empty() && (self.enemies.len() as u32) < self.wave_definition.max_alive {
 			let time_since_last_spawn = self.since_last_spawn.elapsed().as_secs_f32();
-			if time_since_last_spawn > 2.0 {
+			if time_since_last_spawn > self.wave_definition.spawn_interval {
 				log::info!("spawn new mob");
-				self.enemies.push(self.spawner.spawn(state, self.map.get_mob_spawn_point()));
-				self.enemies_spawned += 1;
+				let archetype = self.spawn_queue.pop().unwrap();
+				self.enemies.push(self.spawner.spawn(state, self.map.get_mob_spawn_point(), archetype));
 				self.since_last_spawn = Instant::now();
 			}
 		}
 		self.map.process(state);
+		let hud_state = self.hud_state();
+		self.hud.update(state, hud_state);
 	}
 }
\ No newline at end of file
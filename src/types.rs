@@ -1,25 +1,88 @@
+use pge::ArenaId;
 use pge::MouseEvent;
+use pge::Node;
 use pge::State;
 use pge::Vec3;
+use serde::Deserialize;
+
+use crate::combat::Combat;
+use crate::combat::Shield;
+use crate::effects::StatusEffects;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MobArchetype {
+	/// Runs straight at its target.
+	Melee,
+	/// Keeps its distance and shoots when it can see its target.
+	Ranged,
+}
+
+/// A minion a spell wants spawned. Spawning is up to the game mode since it owns the mobs.
+#[derive(Debug, Clone)]
+pub struct SummonRequest {
+	pub summoner: ArenaId<Node>,
+	pub team: u32,
+	pub translation: Vec3,
+	pub archetype: MobArchetype,
+	pub lifetime: f32,
+}
+
+/// Everything an item needs while it is being processed for its owner.
+pub struct ItemContext<'a> {
+	pub state: &'a mut State,
+	pub combat: &'a mut Combat,
+	pub owner: pge::ArenaId<pge::Node>,
+	pub team: u32,
+	/// Scales weapon spread, lower while crouching or prone.
+	pub spread_multiplier: f32,
+	/// The owner's mana, spent by spells.
+	pub mana: &'a mut f32,
+	pub shield: &'a mut Option<Shield>,
+	pub effects: &'a mut StatusEffects,
+	/// Minions to spawn for the owner.
+	pub summons: &'a mut Vec<SummonRequest>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Crosshair {
+	/// Single dot, for melee weapons.
+	Dot,
+	/// Lines pushed apart by the current spread in radians.
+	Spread(f32),
+}
 
 pub trait Item {
+	/// Shown in the hotbar.
+	fn name(&self) -> &str;
 	fn prepare(&mut self, state: &mut State) {}
 	fn activate(&mut self, state: &mut State, parent_id: pge::ArenaId<pge::Node>) {}
 	fn hide(&mut self, state: &mut State) {}
+	/// Removes everything the item has spawned into the state.
+	fn destroy(&mut self, state: &mut State) {}
 	fn start_primary_action(&mut self, state: &mut State) {}
 	fn stop_primary_action(&mut self, state: &mut State) {}
 	fn start_secondary_action(&mut self, state: &mut State) {}
 	fn stop_secondary_action(&mut self, state: &mut State) {}
 	fn start_third_action(&mut self, state: &mut State) {}
 	fn stop_third_action(&mut self, state: &mut State) {}
-	fn process(&mut self, state: &mut State, dt: f32) {}
+	fn reload(&mut self) {}
+	/// Rounds in the magazine and in reserve, for items that use ammo.
+	fn ammo(&self) -> Option<(u32, u32)> { None }
+	fn crosshair(&self) -> Crosshair { Crosshair::Dot }
+	fn process(&mut self, ctx: &mut ItemContext, dt: f32) {}
 	fn on_mouse_moved(&mut self, dx: f32, dy: f32, state: &mut State) {}
 }
 
 pub trait SurvivalMap {
+	/// Identifies the map in the high score table.
+	fn name(&self) -> &str;
+	fn seed(&self) -> u64;
 	fn get_mob_spawn_point(&mut self) -> Vec3;
 	fn get_player_spawn_point(&mut self) -> Vec3;
 	fn process(&mut self, state: &mut State);
+	/// Frees the meshes and materials the map created. Its nodes go with the scene.
+	fn destroy(&mut self, state: &mut State);
 }
 
 pub trait PVPMap {
@@ -1,6 +1,8 @@
 pub use pge::*;
 
-pub fn load_model(path: &str, state: &mut pge::State) -> ArenaId<Node> {
+/// Loads a glTF model and moves its contents under a new node. Returns the node and the
+/// model, which has to be freed with `unload_model` once the node is no longer needed.
+pub fn load_model(path: &str, state: &mut pge::State) -> (ArenaId<Node>, ArenaId<Model>) {
 	let node = Node::new();
 	let node_id = state.nodes.insert(node);
 	let model_id = state.load_3d_model(path);
@@ -12,7 +14,17 @@ pub fn load_model(path: &str, state: &mut pge::State) -> ArenaId<Node> {
 		}
 	}
 
-	node_id
+	(node_id, model_id)
+}
+
+/// Removes a model loaded with `load_model` together with its node.
+pub fn unload_model(state: &mut State, node_id: ArenaId<Node>, model_id: ArenaId<Model>) {
+	despawn_node(state, node_id);
+	if let Some(model) = state.models.remove(&model_id) {
+		for scene_id in model.scenes {
+			state.scenes.remove(&scene_id);
+		}
+	}
 }
 
 pub fn get_root_node(state: &State, node_id: ArenaId<Node>) -> ArenaId<Node> {
@@ -34,6 +46,184 @@ pub fn get_root_node(state: &State, node_id: ArenaId<Node>) -> ArenaId<Node> {
 	node_id
 }
 
+/// Removes a node together with everything parented under it.
+pub fn despawn_node(state: &mut State, node_id: ArenaId<Node>) {
+	let mut stack = vec![node_id];
+	while let Some(node_id) = stack.pop() {
+		for (child_id, node) in state.nodes.iter() {
+			if node.parent == NodeParent::Node(node_id) {
+				stack.push(child_id);
+			}
+		}
+		state.nodes.remove(&node_id);
+	}
+}
+
+/// Removes a scene with all of its nodes and the lights attached to them.
+pub fn despawn_scene(state: &mut State, scene_id: ArenaId<Scene>) {
+	let mut roots = Vec::new();
+	for (node_id, node) in state.nodes.iter() {
+		if node.parent == NodeParent::Scene(scene_id) {
+			roots.push(node_id);
+		}
+	}
+	for node_id in roots {
+		despawn_node(state, node_id);
+	}
+
+	let mut lights = Vec::new();
+	for (light_id, light) in state.point_lights.iter() {
+		if let Some(node_id) = light.node_id {
+			if state.nodes.get(&node_id).is_none() {
+				lights.push(light_id);
+			}
+		}
+	}
+	for light_id in lights {
+		state.point_lights.remove(&light_id);
+	}
+
+	state.scenes.remove(&scene_id);
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+	pub distance: f32,
+	/// Normal of the face that was hit.
+	pub normal: Vec3,
+}
+
+/// Casts a ray against the static box colliders of a scene and returns the distance
+/// to the closest one. Boxes are treated as axis aligned.
+pub fn cast_ray(state: &State, scene_id: ArenaId<Scene>, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<f32> {
+	cast_ray_hit(state, scene_id, origin, dir, max_distance).map(|hit| hit.distance)
+}
+
+/// Like `cast_ray`, but also returns the normal of the face that was hit. The engine's
+/// `RayCast` only reports hits a frame later, this answers right away for movement and
+/// aiming. Dynamic bodies and shapes other than boxes are not hit.
+pub fn cast_ray_hit(state: &State, scene_id: ArenaId<Scene>, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<RayHit> {
+	closest_box_hit(static_boxes(state, scene_id), origin, dir, max_distance)
+}
+
+/// Center and half size of every static box collider of a scene.
+fn static_boxes(state: &State, scene_id: ArenaId<Scene>) -> impl Iterator<Item = (Vec3, Vec3)> + '_ {
+	state.nodes.iter().filter_map(move |(_, node)| {
+		if node.parent != NodeParent::Scene(scene_id) || node.physics.typ != PhycisObjectType::Static {
+			return None;
+		}
+		match node.collision_shape {
+			Some(CollisionShape::Box { size }) => Some((node.translation, size / 2.0)),
+			_ => None,
+		}
+	})
+}
+
+fn closest_box_hit(boxes: impl IntoIterator<Item = (Vec3, Vec3)>, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<RayHit> {
+	let mut closest: Option<RayHit> = None;
+	for (center, half_size) in boxes {
+		let hit = match ray_box_intersection(origin, dir, center, half_size) {
+			Some(hit) if hit.distance <= max_distance => hit,
+			_ => continue,
+		};
+		if closest.map_or(true, |c| hit.distance < c.distance) {
+			closest = Some(hit);
+		}
+	}
+	closest
+}
+
+/// Slab test of a ray against an axis aligned box. A ray starting inside the box hits
+/// it at distance zero with the normal pointing back along the ray.
+pub fn ray_box_intersection(origin: Vec3, dir: Vec3, center: Vec3, half_size: Vec3) -> Option<RayHit> {
+	let min = center - half_size;
+	let max = center + half_size;
+	let mut t_min = 0.0f32;
+	let mut t_max = f32::INFINITY;
+	let mut normal = -dir.normalize_or_zero();
+
+	for axis in 0..3 {
+		// Parallel to the slab, dividing by zero would give NaN
+		if dir[axis] == 0.0 {
+			if origin[axis] < min[axis] || origin[axis] > max[axis] {
+				return None;
+			}
+			continue;
+		}
+
+		let t1 = (min[axis] - origin[axis]) / dir[axis];
+		let t2 = (max[axis] - origin[axis]) / dir[axis];
+		let (near, far) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+		if near > t_min {
+			// The face entered last is the one that was hit, it faces against the ray
+			t_min = near;
+			normal = Vec3::ZERO;
+			normal[axis] = -dir[axis].signum();
+		}
+		t_max = t_max.min(far);
+		if t_max < t_min {
+			return None;
+		}
+	}
+
+	Some(RayHit { distance: t_min, normal })
+}
+
+pub fn has_line_of_sight(state: &State, scene_id: ArenaId<Scene>, from: Vec3, to: Vec3) -> bool {
+	let distance = from.distance(to);
+	if distance <= 0.0 {
+		return true;
+	}
+	cast_ray(state, scene_id, from, (to - from) / distance, distance).is_none()
+}
+
+/// How far a body may sink into a static box before it counts as overlapping it. The
+/// physics leaves bodies resting slightly inside the floor.
+pub const OVERLAP_SKIN: f32 = 0.05;
+
+/// Whether a box overlaps any of the static box colliders of a scene.
+pub fn overlaps_static(state: &State, scene_id: ArenaId<Scene>, center: Vec3, half_size: Vec3) -> bool {
+	overlaps_any(static_boxes(state, scene_id), center, half_size)
+}
+
+fn overlaps_any(boxes: impl IntoIterator<Item = (Vec3, Vec3)>, center: Vec3, half_size: Vec3) -> bool {
+	boxes.into_iter().any(|(box_center, box_half_size)| {
+		let distance = (box_center - center).abs();
+		let reach = box_half_size + half_size;
+		distance.x < reach.x && distance.y < reach.y && distance.z < reach.z
+	})
+}
+
+/// Finds where a box of `half_size` can be moved to along `dir`, at most `max_distance`
+/// away. Stops short of walls, backs off until the box doesn't overlap anything and
+/// requires ground somewhere below. Returns None if there is no such place.
+pub fn find_safe_destination(state: &State, scene_id: ArenaId<Scene>, origin: Vec3, dir: Vec3, max_distance: f32, half_size: Vec3) -> Option<Vec3> {
+	let boxes: Vec<(Vec3, Vec3)> = static_boxes(state, scene_id).collect();
+	safe_destination(&boxes, origin, dir, max_distance, half_size)
+}
+
+fn safe_destination(boxes: &[(Vec3, Vec3)], origin: Vec3, dir: Vec3, max_distance: f32, half_size: Vec3) -> Option<Vec3> {
+	const STEP: f32 = 0.25;
+
+	let margin = half_size.max_element();
+	let mut distance = match closest_box_hit(boxes.iter().copied(), origin, dir, max_distance) {
+		Some(hit) => hit.distance - margin,
+		None => max_distance,
+	};
+
+	while distance > 0.0 {
+		let destination = origin + dir * distance;
+		// Anything below the floor has nothing under it
+		let has_ground = closest_box_hit(boxes.iter().copied(), destination, Vec3::NEG_Y, f32::MAX).is_some();
+		if has_ground && !overlaps_any(boxes.iter().copied(), destination, half_size - Vec3::splat(OVERLAP_SKIN)) {
+			return Some(destination);
+		}
+		distance -= STEP;
+	}
+
+	None
+}
+
 #[derive(Debug, Clone)]
 pub struct MoveDirection {
 	pub forward: bool,
@@ -78,4 +268,71 @@ impl MoveDirection {
 	pub fn is_moving(&self) -> bool {
 		self.forward || self.backward || self.left || self.right
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ray_hits_top_of_floor() {
+		let hit = ray_box_intersection(Vec3::new(0.0, 5.0, 0.0), Vec3::NEG_Y, Vec3::ZERO, Vec3::new(5.0, 0.5, 5.0)).unwrap();
+		assert!((hit.distance - 4.5).abs() < 1e-5);
+		assert_eq!(hit.normal, Vec3::Y);
+	}
+
+	#[test]
+	fn axis_parallel_ray_beside_box_misses() {
+		let hit = ray_box_intersection(Vec3::new(10.0, 5.0, 0.0), Vec3::NEG_Y, Vec3::ZERO, Vec3::new(5.0, 0.5, 5.0));
+		assert!(hit.is_none());
+	}
+
+	#[test]
+	fn box_behind_ray_is_missed() {
+		let hit = ray_box_intersection(Vec3::new(5.0, 0.0, 0.0), Vec3::X, Vec3::ZERO, Vec3::ONE);
+		assert!(hit.is_none());
+	}
+
+	#[test]
+	fn diagonal_ray_reports_face_entered_last() {
+		let dir = Vec3::new(1.0, -1.0, 0.0).normalize();
+		let hit = ray_box_intersection(Vec3::new(-3.0, 2.0, 0.0), dir, Vec3::ZERO, Vec3::ONE).unwrap();
+		assert!((hit.distance - 2.0 * 2.0f32.sqrt()).abs() < 1e-5);
+		assert_eq!(hit.normal, Vec3::NEG_X);
+	}
+
+	const FLOOR: (Vec3, Vec3) = (Vec3::new(0.0, -0.5, 0.0), Vec3::new(50.0, 0.5, 50.0));
+	const BODY: Vec3 = Vec3::new(0.25, 0.9, 0.25);
+
+	#[test]
+	fn blink_along_flat_floor_goes_the_full_distance() {
+		// Sunk into the floor a little, like the physics leaves a standing body
+		let origin = Vec3::new(0.0, BODY.y - 0.01, 0.0);
+		let destination = safe_destination(&[FLOOR], origin, Vec3::X, 8.0, BODY).unwrap();
+		assert_eq!(destination, origin + Vec3::X * 8.0);
+	}
+
+	#[test]
+	fn blink_into_wall_stops_in_front_of_it() {
+		let wall = (Vec3::new(5.0, 2.0, 0.0), Vec3::new(0.5, 2.0, 5.0));
+		let origin = Vec3::new(0.0, BODY.y - 0.01, 0.0);
+		let destination = safe_destination(&[FLOOR, wall], origin, Vec3::X, 8.0, BODY).unwrap();
+		assert!(destination.x > 0.0);
+		assert!(destination.x + BODY.x <= wall.0.x - wall.1.x);
+	}
+
+	#[test]
+	fn blink_towards_a_ledge_stays_over_ground() {
+		let ledge = (Vec3::new(0.0, -0.5, 0.0), Vec3::new(2.0, 0.5, 2.0));
+		let origin = Vec3::new(0.0, BODY.y, 0.0);
+		let destination = safe_destination(&[ledge], origin, Vec3::X, 8.0, BODY).unwrap();
+		assert!(destination.x <= 2.0);
+	}
+
+	#[test]
+	fn ray_from_inside_hits_at_zero() {
+		let hit = ray_box_intersection(Vec3::ZERO, Vec3::X, Vec3::ZERO, Vec3::ONE).unwrap();
+		assert_eq!(hit.distance, 0.0);
+		assert_eq!(hit.normal, Vec3::NEG_X);
+	}
+}
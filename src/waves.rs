@@ -0,0 +1,190 @@
+use std::path::Path;
+
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::types::MobArchetype;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MobGroup {
+	pub archetype: MobArchetype,
+	pub count: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveDefinition {
+	pub mobs: Vec<MobGroup>,
+	/// Seconds between two spawns.
+	#[serde(default = "default_spawn_interval")]
+	pub spawn_interval: f32,
+	/// How many mobs of this wave may be alive at the same time.
+	#[serde(default = "default_max_alive")]
+	pub max_alive: u32,
+	/// Seconds of rest after this wave is cleared.
+	#[serde(default = "default_intermission")]
+	pub intermission: f32,
+}
+
+fn default_spawn_interval() -> f32 {
+	2.0
+}
+
+fn default_max_alive() -> u32 {
+	20
+}
+
+fn default_intermission() -> f32 {
+	5.0
+}
+
+impl WaveDefinition {
+	pub fn total_mobs(&self) -> u32 {
+		self.mobs.iter().map(|g| g.count).sum()
+	}
+
+	/// All mobs of the wave in a random spawn order.
+	pub fn spawn_queue(&self) -> Vec<MobArchetype> {
+		let mut queue = Vec::new();
+		for group in &self.mobs {
+			for _ in 0..group.count {
+				queue.push(group.archetype);
+			}
+		}
+		queue.shuffle(&mut rand::thread_rng());
+		queue
+	}
+}
+
+/// Formula used for every wave past the ones listed in the config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EndlessWaves {
+	pub base_count: u32,
+	pub count_per_wave: u32,
+	pub ranged_fraction: f32,
+	pub spawn_interval: f32,
+	pub spawn_interval_step: f32,
+	pub min_spawn_interval: f32,
+	pub max_alive: u32,
+	pub intermission: f32,
+}
+
+impl Default for EndlessWaves {
+	fn default() -> Self {
+		Self {
+			base_count: 10,
+			count_per_wave: 5,
+			ranged_fraction: 0.3,
+			spawn_interval: default_spawn_interval(),
+			spawn_interval_step: 0.0,
+			min_spawn_interval: 0.5,
+			max_alive: default_max_alive(),
+			intermission: default_intermission(),
+		}
+	}
+}
+
+impl EndlessWaves {
+	/// `index` counts from 1 for the first wave after the ones listed in the config.
+	pub fn wave(&self, index: u32) -> WaveDefinition {
+		let step = index.saturating_sub(1);
+		let count = self.base_count + self.count_per_wave * step;
+		let ranged = (count as f32 * self.ranged_fraction).round() as u32;
+		let spawn_interval = (self.spawn_interval - self.spawn_interval_step * step as f32).max(self.min_spawn_interval);
+
+		WaveDefinition {
+			mobs: vec![
+				MobGroup { archetype: MobArchetype::Melee, count: count - ranged.min(count) },
+				MobGroup { archetype: MobArchetype::Ranged, count: ranged.min(count) },
+			],
+			spawn_interval,
+			max_alive: self.max_alive,
+			intermission: self.intermission,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WaveConfig {
+	/// Every Nth wave starts with a boss alongside its mobs. Zero disables bosses.
+	pub boss_every: u32,
+	pub waves: Vec<WaveDefinition>,
+	pub endless: EndlessWaves,
+}
+
+impl Default for WaveConfig {
+	fn default() -> Self {
+		Self {
+			boss_every: 5,
+			waves: Vec::new(),
+			endless: EndlessWaves::default(),
+		}
+	}
+}
+
+impl WaveConfig {
+	pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+		let content = std::fs::read_to_string(path)?;
+		Self::parse(&content)
+	}
+
+	pub fn parse(content: &str) -> anyhow::Result<Self> {
+		let config: WaveConfig = toml::from_str(content)?;
+		// Nothing would ever spawn and the wave could not be cleared
+		for (i, wave) in config.waves.iter().enumerate() {
+			if wave.max_alive == 0 {
+				anyhow::bail!("wave {} has max_alive = 0", i + 1);
+			}
+		}
+		if config.endless.max_alive == 0 {
+			anyhow::bail!("endless waves have max_alive = 0");
+		}
+		Ok(config)
+	}
+
+	/// Waves are numbered from 1.
+	pub fn wave(&self, number: u32) -> WaveDefinition {
+		match self.waves.get(number.saturating_sub(1) as usize) {
+			Some(wave) => wave.clone(),
+			None => self.endless.wave(number - self.waves.len() as u32),
+		}
+	}
+
+	pub fn is_boss_wave(&self, number: u32) -> bool {
+		self.boss_every > 0 && number % self.boss_every == 0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn shipped() -> WaveConfig {
+		WaveConfig::load(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/waves.toml")).unwrap()
+	}
+
+	#[test]
+	fn shipped_config_parses() {
+		let config = shipped();
+		assert!(!config.waves.is_empty());
+		assert!(config.waves.iter().all(|w| w.total_mobs() > 0));
+	}
+
+	#[test]
+	fn endless_waves_continue_from_the_last_listed_wave() {
+		let config = shipped();
+		let first_endless = config.wave(config.waves.len() as u32 + 1);
+		assert_eq!(first_endless.total_mobs(), config.endless.base_count);
+		assert_eq!(first_endless.spawn_interval, config.endless.spawn_interval);
+
+		let second_endless = config.wave(config.waves.len() as u32 + 2);
+		assert_eq!(second_endless.total_mobs(), config.endless.base_count + config.endless.count_per_wave);
+	}
+
+	#[test]
+	fn zero_max_alive_is_rejected() {
+		let content = "[[waves]]\nmax_alive = 0\nmobs = [{ archetype = \"melee\", count = 1 }]\n";
+		assert!(WaveConfig::parse(content).is_err());
+	}
+}